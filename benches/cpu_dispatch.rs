@@ -0,0 +1,80 @@
+//! Instructions per second of the cpu core, `cargo bench --bench cpu_dispatch`.
+//! `flat ram` is the dispatch path alone, `nestest` also ticks the PPU on every cycle
+
+use std::{hint::black_box, path::Path, time::Instant};
+
+use dendynes::{
+    cartridge::Cartridge,
+    cpu::{bus::FlatRam, processor::{StatusFlags, CPU}},
+    nes::Nes,
+};
+
+const FLAT_RAM_INSTRUCTIONS: u64 = 20_000_000;
+const NESTEST_RUNS: u64 = 200;
+const NESTEST_INSTRUCTIONS: u64 = 8990;
+const PROGRAM_START: u16 = 0x0200;
+
+// loops forever over loads, stores, arithmetic, shifts, a read-modify-write and branches
+const PROGRAM: [u8; 28] = [
+    0xA2, 0x00,       // LDX #$00
+    0xA9, 0x10,       // LDA #$10
+    0x69, 0x03,       // ADC #$03
+    0x95, 0x40,       // STA $40,X
+    0x5D, 0x00, 0x03, // EOR $0300,X
+    0x0A,             // ASL A
+    0xE6, 0x41,       // INC $41
+    0xC8,             // INY
+    0x24, 0x41,       // BIT $41
+    0x48,             // PHA
+    0x68,             // PLA
+    0xE8,             // INX
+    0xE0, 0x20,       // CPX #$20
+    0xD0, 0xEC,       // BNE $0204
+    0x4C, 0x00, 0x02, // JMP $0200
+    0xEA,             // NOP
+];
+
+fn report(name: &str, instructions: u64, seconds: f64) {
+    println!(
+        "{:<10} {:>12} instructions in {:>7.3} s, {:>7.2} M instructions/s",
+        name, instructions, seconds, instructions as f64 / seconds / 1_000_000.0,
+    );
+}
+
+fn flat_ram() {
+    let mut ram = FlatRam::new();
+    ram.load(PROGRAM_START, &PROGRAM);
+    let mut cpu = CPU::new(ram);
+    cpu.program_pointer = PROGRAM_START as usize;
+
+    let start = Instant::now();
+    for _ in 0..FLAT_RAM_INSTRUCTIONS {
+        black_box(cpu.cpu_step().unwrap());
+    }
+    report("flat ram", FLAT_RAM_INSTRUCTIONS, start.elapsed().as_secs_f64());
+}
+
+fn nestest() {
+    let cartridge = Cartridge::from_path(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/roms/nestest.nes")).unwrap();
+    let mut nes = Nes::new(cartridge);
+
+    let mut seconds = 0.0;
+    for _ in 0..NESTEST_RUNS {
+        // rebuilding the console is not part of the measurement
+        nes.power_cycle();
+        nes.cpu.program_pointer = 0xC000;
+        nes.cpu.status = StatusFlags::from_bits_truncate(0x24);
+
+        let start = Instant::now();
+        for _ in 0..NESTEST_INSTRUCTIONS {
+            black_box(nes.step_instruction().unwrap());
+        }
+        seconds += start.elapsed().as_secs_f64();
+    }
+    report("nestest", NESTEST_RUNS * NESTEST_INSTRUCTIONS, seconds);
+}
+
+fn main() {
+    flat_ram();
+    nestest();
+}
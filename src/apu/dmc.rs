@@ -0,0 +1,168 @@
+use crate::region::Region;
+
+// output periods in cpu cycles, indexed by the low nibble of $4010
+const NTSC_RATES: [u16; 16] = [428, 380, 340, 320, 286, 254, 226, 214, 190, 160, 142, 128, 106, 84, 72, 54];
+const PAL_RATES: [u16; 16] = [398, 354, 316, 298, 276, 236, 210, 198, 176, 148, 132, 118, 98, 78, 66, 50];
+
+const SAMPLE_ADDRESS_BASE: u16 = 0xC000;
+const MAX_OUTPUT_LEVEL: u8 = 127;
+
+/// Delta modulation channel at $4010-$4013. Plays 1-bit deltas fetched by DMA from $8000-$FFFF,
+/// the bus does the fetches and stalls the cpu for them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dmc {
+    pub irq_enabled: bool,
+    pub looping: bool,
+    pub interrupt: bool,
+    pub timer_period: u16,
+    // 7 bit dac, also loaded directly through $4011
+    pub output_level: u8,
+    pub sample_address: u16,
+    pub sample_length: u16,
+    pub current_address: u16,
+    pub bytes_remaining: u16,
+    rates: [u16; 16],
+    timer: u16,
+    sample_buffer: Option<u8>,
+    shift_register: u8,
+    bits_remaining: u8,
+    silence: bool,
+}
+
+impl Dmc {
+    pub fn new(region: Region) -> Self {
+        // dendy runs the apu off the ntsc divider
+        let rates = match region {
+            Region::Pal => PAL_RATES,
+            Region::Ntsc | Region::Dendy => NTSC_RATES,
+        };
+
+        return Dmc {
+            irq_enabled: false,
+            looping: false,
+            interrupt: false,
+            timer_period: rates[0],
+            output_level: 0,
+            sample_address: SAMPLE_ADDRESS_BASE,
+            sample_length: 1,
+            current_address: SAMPLE_ADDRESS_BASE,
+            bytes_remaining: 0,
+            rates: rates,
+            timer: 0,
+            sample_buffer: None,
+            shift_register: 0,
+            bits_remaining: 8,
+            silence: true,
+        };
+    }
+
+    /// `register` is the address offset inside the channel, 0 to 3
+    pub fn write_register(&mut self, register: usize, value: u8) {
+        match register {
+            0 => {
+                self.irq_enabled = value & 0b1000_0000 > 0;
+                self.looping = value & 0b0100_0000 > 0;
+                self.timer_period = self.rates[(value & 0x0F) as usize];
+
+                if !self.irq_enabled {
+                    self.interrupt = false;
+                }
+            },
+            1 => {
+                self.output_level = value & MAX_OUTPUT_LEVEL;
+            },
+            2 => {
+                self.sample_address = SAMPLE_ADDRESS_BASE + ((value as u16) << 6);
+            },
+            3 => {
+                self.sample_length = ((value as u16) << 4) + 1;
+            },
+            _ => unreachable!("dmc register {}", register),
+        }
+    }
+
+    /// Bit 4 of $4015: stops the sample, or starts it over when it has finished
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if !enabled {
+            self.bytes_remaining = 0;
+        } else if self.bytes_remaining == 0 {
+            self.restart();
+        }
+    }
+
+    fn restart(&mut self) {
+        self.current_address = self.sample_address;
+        self.bytes_remaining = self.sample_length;
+    }
+
+    pub fn is_active(&self) -> bool {
+        return self.bytes_remaining > 0;
+    }
+
+    /// Address of the next sample byte while the buffer waits for one
+    pub fn dma_address(&self) -> Option<u16> {
+        if self.sample_buffer.is_none() && self.bytes_remaining > 0 {
+            return Some(self.current_address);
+        }
+
+        return None;
+    }
+
+    /// Byte fetched from `dma_address`
+    pub fn load_sample(&mut self, value: u8) {
+        self.sample_buffer = Some(value);
+        // the address wraps to $8000, not to $0000
+        self.current_address = self.current_address.checked_add(1).unwrap_or(0x8000);
+        self.bytes_remaining -= 1;
+
+        if self.bytes_remaining == 0 {
+            if self.looping {
+                self.restart();
+            } else if self.irq_enabled {
+                self.interrupt = true;
+            }
+        }
+    }
+
+    /// Clocked every cpu cycle, the rate table is in cpu cycles
+    pub fn clock_timer(&mut self) {
+        if self.timer > 0 {
+            self.timer -= 1;
+
+            return;
+        }
+
+        self.timer = self.timer_period - 1;
+
+        if !self.silence {
+            if self.shift_register & 1 > 0 {
+                if self.output_level <= MAX_OUTPUT_LEVEL - 2 {
+                    self.output_level += 2;
+                }
+            } else if self.output_level >= 2 {
+                self.output_level -= 2;
+            }
+        }
+        self.shift_register >>= 1;
+
+        self.bits_remaining -= 1;
+        if self.bits_remaining == 0 {
+            self.bits_remaining = 8;
+
+            match self.sample_buffer.take() {
+                Some(sample) => {
+                    self.shift_register = sample;
+                    self.silence = false;
+                },
+                None => {
+                    self.silence = true;
+                },
+            }
+        }
+    }
+
+    /// Current level, 0 to 127
+    pub fn output(&self) -> u8 {
+        return self.output_level;
+    }
+}
@@ -0,0 +1,65 @@
+/// Volume of the pulse and noise channels: either a constant or a sawtooth decaying from 15,
+/// one step per `volume + 1` quarter frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Envelope {
+    pub start: bool,
+    // the same bit halts the length counter
+    pub looping: bool,
+    pub constant_volume: bool,
+    // constant volume, or the divider period of the decay
+    pub volume: u8,
+    divider: u8,
+    decay: u8,
+}
+
+impl Envelope {
+    pub fn new() -> Self {
+        return Envelope {
+            start: false,
+            looping: false,
+            constant_volume: false,
+            volume: 0,
+            divider: 0,
+            decay: 0,
+        };
+    }
+
+    /// `--LC VVVV` of the channel's first register
+    pub fn write(&mut self, value: u8) {
+        self.looping = value & 0b0010_0000 > 0;
+        self.constant_volume = value & 0b0001_0000 > 0;
+        self.volume = value & 0x0F;
+    }
+
+    /// Quarter frame clock
+    pub fn clock(&mut self) {
+        if self.start {
+            self.start = false;
+            self.decay = 15;
+            self.divider = self.volume;
+
+            return;
+        }
+
+        if self.divider > 0 {
+            self.divider -= 1;
+
+            return;
+        }
+
+        self.divider = self.volume;
+        if self.decay > 0 {
+            self.decay -= 1;
+        } else if self.looping {
+            self.decay = 15;
+        }
+    }
+
+    pub fn output(&self) -> u8 {
+        if self.constant_volume {
+            return self.volume;
+        }
+
+        return self.decay;
+    }
+}
@@ -0,0 +1,76 @@
+use std::f32::consts::PI;
+
+// the RC stages between the 2A03 and the console's audio jack
+const FIRST_HIGH_PASS_HZ: f32 = 90.0;
+const SECOND_HIGH_PASS_HZ: f32 = 440.0;
+const LOW_PASS_HZ: f32 = 14_000.0;
+
+/// First order RC filter, run at the output sample rate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Filter {
+    pub high_pass: bool,
+    alpha: f32,
+    previous_input: f32,
+    previous_output: f32,
+}
+
+impl Filter {
+    pub fn high_pass(sample_rate: u32, cutoff_hz: f32) -> Self {
+        let rc = 1.0 / (2.0 * PI * cutoff_hz);
+        let dt = 1.0 / sample_rate as f32;
+
+        return Filter {
+            high_pass: true,
+            alpha: rc / (rc + dt),
+            previous_input: 0.0,
+            previous_output: 0.0,
+        };
+    }
+
+    pub fn low_pass(sample_rate: u32, cutoff_hz: f32) -> Self {
+        let rc = 1.0 / (2.0 * PI * cutoff_hz);
+        let dt = 1.0 / sample_rate as f32;
+
+        return Filter {
+            high_pass: false,
+            alpha: dt / (rc + dt),
+            previous_input: 0.0,
+            previous_output: 0.0,
+        };
+    }
+
+    pub fn process(&mut self, input: f32) -> f32 {
+        let output = if self.high_pass {
+            self.alpha * (self.previous_output + input - self.previous_input)
+        } else {
+            self.previous_output + self.alpha * (input - self.previous_output)
+        };
+
+        self.previous_input = input;
+        self.previous_output = output;
+
+        return output;
+    }
+}
+
+/// Two high-passes take the dc offset and the rumble out, the low-pass the hiss above 14 kHz
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FilterChain {
+    pub filters: [Filter; 3],
+}
+
+impl FilterChain {
+    pub fn new(sample_rate: u32) -> Self {
+        return FilterChain {
+            filters: [
+                Filter::high_pass(sample_rate, FIRST_HIGH_PASS_HZ),
+                Filter::high_pass(sample_rate, SECOND_HIGH_PASS_HZ),
+                Filter::low_pass(sample_rate, LOW_PASS_HZ),
+            ],
+        };
+    }
+
+    pub fn process(&mut self, input: f32) -> f32 {
+        return self.filters.iter_mut().fold(input, |sample, filter| filter.process(sample));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::region::Region;
+
+// cpu cycles since the sequence started at which each step happens, the last entry restarts it
+const NTSC_FOUR_STEP: [u32; 6] = [7457, 14913, 22371, 29828, 29829, 29830];
+const NTSC_FIVE_STEP: [u32; 6] = [7457, 14913, 22371, 29829, 37281, 37282];
+const PAL_FOUR_STEP: [u32; 6] = [8313, 16627, 24939, 33252, 33253, 33254];
+const PAL_FIVE_STEP: [u32; 6] = [8313, 16627, 24939, 33253, 41565, 41566];
+
+/// Which units a frame counter step clocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FrameClocks {
+    // envelopes and the triangle linear counter
+    pub quarter: bool,
+    // length counters and sweeps
+    pub half: bool,
+}
+
+impl FrameClocks {
+    const NONE: FrameClocks = FrameClocks { quarter: false, half: false };
+    const QUARTER: FrameClocks = FrameClocks { quarter: true, half: false };
+    const BOTH: FrameClocks = FrameClocks { quarter: true, half: true };
+}
+
+/// The $4017 sequencer, drives the channel units roughly 240 times a second and raises the
+/// frame IRQ at the end of the 4-step sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameCounter {
+    pub five_step: bool,
+    pub irq_inhibit: bool,
+    pub interrupt: bool,
+    steps: [[u32; 6]; 2],
+    cycles: u32,
+    step: usize,
+    // a $4017 write restarts the sequence 3 or 4 cpu cycles later
+    restart_delay: u8,
+}
+
+impl FrameCounter {
+    pub fn new(region: Region) -> Self {
+        // dendy runs the apu off the ntsc divider
+        let steps = match region {
+            Region::Pal => [PAL_FOUR_STEP, PAL_FIVE_STEP],
+            Region::Ntsc | Region::Dendy => [NTSC_FOUR_STEP, NTSC_FIVE_STEP],
+        };
+
+        return FrameCounter {
+            five_step: false,
+            irq_inhibit: false,
+            interrupt: false,
+            steps: steps,
+            cycles: 0,
+            step: 0,
+            restart_delay: 0,
+        };
+    }
+
+    /// `MI-- ----` of $4017, `odd_cycle` is the parity of the cpu cycle doing the write
+    pub fn write(&mut self, value: u8, odd_cycle: bool) {
+        self.five_step = value & 0b1000_0000 > 0;
+        self.irq_inhibit = value & 0b0100_0000 > 0;
+
+        if self.irq_inhibit {
+            self.interrupt = false;
+        }
+
+        self.restart_delay = if odd_cycle { 4 } else { 3 };
+    }
+
+    /// Clocked every cpu cycle
+    pub fn tick(&mut self) -> FrameClocks {
+        if self.restart_delay > 0 {
+            self.restart_delay -= 1;
+
+            if self.restart_delay == 0 {
+                self.cycles = 0;
+                self.step = 0;
+
+                // the 5-step mode clocks everything right away
+                return if self.five_step { FrameClocks::BOTH } else { FrameClocks::NONE };
+            }
+        }
+
+        self.cycles += 1;
+        if self.cycles != self.steps[self.five_step as usize][self.step] {
+            return FrameClocks::NONE;
+        }
+
+        let clocks = match self.step {
+            0 | 2 => FrameClocks::QUARTER,
+            1 | 4 => FrameClocks::BOTH,
+            _ => FrameClocks::NONE,
+        };
+
+        if !self.five_step && self.step >= 3 && !self.irq_inhibit {
+            self.interrupt = true;
+        }
+
+        self.step += 1;
+        if self.step == self.steps[0].len() {
+            self.step = 0;
+            self.cycles = 0;
+        }
+
+        return clocks;
+    }
+
+    pub fn reset(&mut self) {
+        self.interrupt = false;
+        self.cycles = 0;
+        self.step = 0;
+        self.restart_delay = 0;
+    }
+}
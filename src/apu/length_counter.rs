@@ -0,0 +1,50 @@
+// lengths in half frames, indexed by bits 7-3 of the channel's last register
+const LENGTH_TABLE: [u8; 32] = [
+    10, 254, 20, 2, 40, 4, 80, 6, 160, 8, 60, 10, 14, 12, 26, 14,
+    12, 16, 24, 18, 48, 20, 96, 22, 192, 24, 72, 26, 16, 28, 32, 30,
+];
+
+/// Silences a channel after a number of half frames, unless halted.
+/// Shared by every channel but the DMC
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LengthCounter {
+    pub counter: u8,
+    pub halt: bool,
+    // the channel's bit in $4015, a disabled counter stays at zero
+    pub enabled: bool,
+}
+
+impl LengthCounter {
+    pub fn new() -> Self {
+        return LengthCounter {
+            counter: 0,
+            halt: false,
+            enabled: false,
+        };
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+
+        if !enabled {
+            self.counter = 0;
+        }
+    }
+
+    pub fn load(&mut self, index: u8) {
+        if self.enabled {
+            self.counter = LENGTH_TABLE[(index & 0x1F) as usize];
+        }
+    }
+
+    /// Half frame clock
+    pub fn clock(&mut self) {
+        if self.counter > 0 && !self.halt {
+            self.counter -= 1;
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        return self.counter > 0;
+    }
+}
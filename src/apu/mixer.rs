@@ -0,0 +1,26 @@
+// the two dacs of the 2A03 are not linear, both outputs come from these tables
+// pulse: 95.52 / (8128 / n + 100), n = pulse 1 + pulse 2
+static PULSE_TABLE: [f32; 31] = mix_table(95.52, 8128.0);
+// triangle, noise and dmc: 163.67 / (24329 / n + 100), n = 3 * triangle + 2 * noise + dmc
+static TND_TABLE: [f32; 203] = mix_table(163.67, 24329.0);
+
+const fn mix_table<const N: usize>(gain: f32, divisor: f32) -> [f32; N] {
+    let mut table = [0.0; N];
+
+    // n = 0 is silence, the formula divides by zero there
+    let mut n = 1;
+    while n < N {
+        table[n] = gain / (divisor / n as f32 + 100.0);
+        n += 1;
+    }
+
+    return table;
+}
+
+/// Level of the audio output pin for the channel outputs, 0.0 to about 1.0
+pub fn mix(pulse_1: u8, pulse_2: u8, triangle: u8, noise: u8, dmc: u8) -> f32 {
+    let pulse = PULSE_TABLE[(pulse_1 + pulse_2) as usize];
+    let tnd = TND_TABLE[3 * triangle as usize + 2 * noise as usize + dmc as usize];
+
+    return pulse + tnd;
+}
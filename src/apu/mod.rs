@@ -0,0 +1,239 @@
+pub mod dmc;
+pub mod envelope;
+pub mod filter;
+pub mod frame_counter;
+pub mod length_counter;
+pub mod mixer;
+pub mod noise;
+pub mod pulse;
+pub mod resampler;
+pub mod triangle;
+
+use bitflags::bitflags;
+
+use self::{
+    dmc::Dmc,
+    filter::FilterChain,
+    frame_counter::{FrameClocks, FrameCounter},
+    noise::Noise,
+    pulse::Pulse,
+    resampler::Resampler,
+    triangle::Triangle,
+};
+use super::{clock::MasterClock, region::Region};
+
+pub const DEFAULT_SAMPLE_RATE: u32 = 44_100;
+
+const PULSE_1_START: usize = 0x4000;
+const PULSE_1_END: usize = 0x4003;
+const PULSE_2_START: usize = 0x4004;
+const PULSE_2_END: usize = 0x4007;
+const TRIANGLE_START: usize = 0x4008;
+const TRIANGLE_END: usize = 0x400B;
+const NOISE_START: usize = 0x400C;
+const NOISE_END: usize = 0x400F;
+const DMC_START: usize = 0x4010;
+const DMC_END: usize = 0x4013;
+const STATUS_ADDRESS: usize = 0x4015;
+const FRAME_COUNTER_ADDRESS: usize = 0x4017;
+
+bitflags! {
+    /// $4015, channel enables when written, length counters and interrupts when read
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ApuStatus: u8 {
+        const PULSE_1         = 0b0000_0001;
+        const PULSE_2         = 0b0000_0010;
+        const TRIANGLE        = 0b0000_0100;
+        const NOISE           = 0b0000_1000;
+        const DMC             = 0b0001_0000;
+        const FRAME_INTERRUPT = 0b0100_0000;
+        const DMC_INTERRUPT   = 0b1000_0000;
+    }
+}
+
+pub struct APU {
+    pub pulses: [Pulse; 2],
+    pub triangle: Triangle,
+    pub noise: Noise,
+    pub dmc: Dmc,
+    pub frame_counter: FrameCounter,
+    // pulse timers only move on every other cpu cycle
+    odd_cycle: bool,
+    // mixer output on the last cpu cycle
+    level: f32,
+    resampler: Resampler,
+    filters: FilterChain,
+    samples: Vec<f32>,
+}
+
+impl APU {
+    pub fn new(region: Region) -> Self {
+        return APU {
+            pulses: [Pulse::new(true), Pulse::new(false)],
+            triangle: Triangle::new(),
+            noise: Noise::new(region),
+            dmc: Dmc::new(region),
+            frame_counter: FrameCounter::new(region),
+            odd_cycle: false,
+            level: 0.0,
+            resampler: Resampler::new(MasterClock::new(region).cpu_clock_hz(), DEFAULT_SAMPLE_RATE),
+            filters: FilterChain::new(DEFAULT_SAMPLE_RATE),
+            samples: Vec::new(),
+        };
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        return self.resampler.sample_rate;
+    }
+
+    /// Starts the audio output over at another rate, samples not drained yet are dropped
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.resampler = Resampler::new(self.resampler.clock_rate, sample_rate);
+        self.filters = FilterChain::new(sample_rate);
+        self.level = 0.0;
+    }
+
+    /// Produces `factor` times the samples per second, audio frontends keep their buffers
+    /// from running dry or over with it
+    pub fn set_rate_adjustment(&mut self, factor: f64) {
+        self.resampler.set_rate_adjustment(factor);
+    }
+
+    /// Filtered samples produced since the last call, mono, -1.0..=1.0
+    pub fn drain_samples(&mut self) -> &[f32] {
+        self.samples.clear();
+        self.resampler.read_samples(&mut self.samples);
+
+        for sample in self.samples.iter_mut() {
+            *sample = self.filters.process(*sample);
+        }
+
+        return &self.samples;
+    }
+
+    /// RESET silences every channel and restarts the frame counter in its last mode
+    pub fn reset(&mut self) {
+        self.write_status(0);
+        self.frame_counter.reset();
+    }
+
+    pub fn write_register(&mut self, address: usize, value: u8) {
+        match address {
+            PULSE_1_START..=PULSE_1_END => {
+                self.pulses[0].write_register(address - PULSE_1_START, value);
+            },
+            PULSE_2_START..=PULSE_2_END => {
+                self.pulses[1].write_register(address - PULSE_2_START, value);
+            },
+            TRIANGLE_START..=TRIANGLE_END => {
+                self.triangle.write_register(address - TRIANGLE_START, value);
+            },
+            NOISE_START..=NOISE_END => {
+                self.noise.write_register(address - NOISE_START, value);
+            },
+            DMC_START..=DMC_END => {
+                self.dmc.write_register(address - DMC_START, value);
+            },
+            STATUS_ADDRESS => {
+                self.write_status(value);
+            },
+            FRAME_COUNTER_ADDRESS => {
+                self.frame_counter.write(value, self.odd_cycle);
+            },
+            _ => {},
+        }
+    }
+
+    fn write_status(&mut self, value: u8) {
+        let status = ApuStatus::from_bits_truncate(value);
+
+        self.pulses[0].length_counter.set_enabled(status.contains(ApuStatus::PULSE_1));
+        self.pulses[1].length_counter.set_enabled(status.contains(ApuStatus::PULSE_2));
+        self.triangle.length_counter.set_enabled(status.contains(ApuStatus::TRIANGLE));
+        self.noise.length_counter.set_enabled(status.contains(ApuStatus::NOISE));
+        self.dmc.set_enabled(status.contains(ApuStatus::DMC));
+        // any write acknowledges the dmc interrupt
+        self.dmc.interrupt = false;
+    }
+
+    /// $4015 read, acknowledges the frame interrupt but not the dmc one
+    pub fn read_status(&mut self) -> u8 {
+        let status = self.peek_status();
+        self.frame_counter.interrupt = false;
+
+        return status;
+    }
+
+    pub fn peek_status(&self) -> u8 {
+        let mut status = ApuStatus::empty();
+
+        status.set(ApuStatus::PULSE_1, self.pulses[0].length_counter.is_active());
+        status.set(ApuStatus::PULSE_2, self.pulses[1].length_counter.is_active());
+        status.set(ApuStatus::TRIANGLE, self.triangle.length_counter.is_active());
+        status.set(ApuStatus::NOISE, self.noise.length_counter.is_active());
+        status.set(ApuStatus::DMC, self.dmc.is_active());
+        status.set(ApuStatus::FRAME_INTERRUPT, self.frame_counter.interrupt);
+        status.set(ApuStatus::DMC_INTERRUPT, self.dmc.interrupt);
+
+        return status.bits();
+    }
+
+    /// Level of the frame counter's line into /IRQ
+    pub fn frame_interrupt(&self) -> bool {
+        return self.frame_counter.interrupt;
+    }
+
+    /// Level of the dmc's line into /IRQ
+    pub fn dmc_interrupt(&self) -> bool {
+        return self.dmc.interrupt;
+    }
+
+    /// Clocked every cpu cycle
+    pub fn tick(&mut self) {
+        let clocks = self.frame_counter.tick();
+        self.clock_frame(clocks);
+
+        self.triangle.clock_timer();
+        self.noise.clock_timer();
+        self.dmc.clock_timer();
+
+        if self.odd_cycle {
+            for pulse in self.pulses.iter_mut() {
+                pulse.clock_timer();
+            }
+        }
+
+        self.odd_cycle = !self.odd_cycle;
+
+        let level = mixer::mix(
+            self.pulses[0].output(),
+            self.pulses[1].output(),
+            self.triangle.output(),
+            self.noise.output(),
+            self.dmc.output(),
+        );
+        if level != self.level {
+            self.resampler.add_delta(level - self.level);
+            self.level = level;
+        }
+        self.resampler.clock();
+    }
+
+    fn clock_frame(&mut self, clocks: FrameClocks) {
+        if clocks.quarter {
+            for pulse in self.pulses.iter_mut() {
+                pulse.clock_quarter_frame();
+            }
+            self.triangle.clock_quarter_frame();
+            self.noise.clock_quarter_frame();
+        }
+
+        if clocks.half {
+            for pulse in self.pulses.iter_mut() {
+                pulse.clock_half_frame();
+            }
+            self.triangle.clock_half_frame();
+            self.noise.clock_half_frame();
+        }
+    }
+}
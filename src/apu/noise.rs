@@ -0,0 +1,94 @@
+use crate::region::Region;
+
+use super::{envelope::Envelope, length_counter::LengthCounter};
+
+// timer periods in cpu cycles, indexed by the low nibble of $400E
+const NTSC_PERIODS: [u16; 16] = [4, 8, 16, 32, 64, 96, 128, 160, 202, 254, 380, 508, 762, 1016, 2034, 4068];
+const PAL_PERIODS: [u16; 16] = [4, 8, 14, 30, 60, 88, 118, 148, 188, 236, 354, 472, 708, 944, 1890, 3778];
+
+/// Pseudo-random channel at $400C-$400F
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Noise {
+    pub envelope: Envelope,
+    pub length_counter: LengthCounter,
+    // feedback from bit 6 instead of bit 1, a 93 step metallic loop instead of 32767 steps of hiss
+    pub short_mode: bool,
+    pub timer_period: u16,
+    periods: [u16; 16],
+    timer: u16,
+    // 15 bit linear feedback shift register, the channel is silent while bit 0 is set
+    shift_register: u16,
+}
+
+impl Noise {
+    pub fn new(region: Region) -> Self {
+        // dendy runs the apu off the ntsc divider
+        let periods = match region {
+            Region::Pal => PAL_PERIODS,
+            Region::Ntsc | Region::Dendy => NTSC_PERIODS,
+        };
+
+        return Noise {
+            envelope: Envelope::new(),
+            length_counter: LengthCounter::new(),
+            short_mode: false,
+            timer_period: periods[0],
+            periods: periods,
+            timer: 0,
+            shift_register: 1,
+        };
+    }
+
+    /// `register` is the address offset inside the channel, 0 to 3
+    pub fn write_register(&mut self, register: usize, value: u8) {
+        match register {
+            0 => {
+                self.length_counter.halt = value & 0b0010_0000 > 0;
+                self.envelope.write(value);
+            },
+            // $400D is not connected
+            1 => {},
+            2 => {
+                self.short_mode = value & 0b1000_0000 > 0;
+                self.timer_period = self.periods[(value & 0x0F) as usize];
+            },
+            3 => {
+                self.length_counter.load(value >> 3);
+                self.envelope.start = true;
+            },
+            _ => unreachable!("noise register {}", register),
+        }
+    }
+
+    /// Clocked every cpu cycle, the period table is in cpu cycles
+    pub fn clock_timer(&mut self) {
+        if self.timer > 0 {
+            self.timer -= 1;
+
+            return;
+        }
+
+        self.timer = self.timer_period - 1;
+
+        let tap = if self.short_mode { 6 } else { 1 };
+        let feedback = (self.shift_register ^ (self.shift_register >> tap)) & 1;
+        self.shift_register = (self.shift_register >> 1) | (feedback << 14);
+    }
+
+    pub fn clock_quarter_frame(&mut self) {
+        self.envelope.clock();
+    }
+
+    pub fn clock_half_frame(&mut self) {
+        self.length_counter.clock();
+    }
+
+    /// Current volume, 0 to 15
+    pub fn output(&self) -> u8 {
+        if !self.length_counter.is_active() || self.shift_register & 1 > 0 {
+            return 0;
+        }
+
+        return self.envelope.output();
+    }
+}
@@ -0,0 +1,166 @@
+use super::{envelope::Envelope, length_counter::LengthCounter};
+
+// 12.5%, 25%, 50% and 25% negated, in the order the sequencer plays them
+const DUTY_SEQUENCES: [[u8; 8]; 4] = [
+    [0, 1, 0, 0, 0, 0, 0, 0],
+    [0, 1, 1, 0, 0, 0, 0, 0],
+    [0, 1, 1, 1, 1, 0, 0, 0],
+    [1, 0, 0, 1, 1, 1, 1, 1],
+];
+
+// shorter periods are above hearing, the channel is muted instead
+const MIN_TIMER_PERIOD: u16 = 8;
+const MAX_TIMER_PERIOD: u16 = 0x7FF;
+
+/// Bends the pulse period up or down every few half frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Sweep {
+    pub enabled: bool,
+    pub period: u8,
+    pub negate: bool,
+    pub shift: u8,
+    pub reload: bool,
+    // pulse 1 negates with ones' complement, subtracting one more than pulse 2
+    pub ones_complement: bool,
+    divider: u8,
+}
+
+impl Sweep {
+    pub fn new(ones_complement: bool) -> Self {
+        return Sweep {
+            enabled: false,
+            period: 0,
+            negate: false,
+            shift: 0,
+            reload: false,
+            ones_complement: ones_complement,
+            divider: 0,
+        };
+    }
+
+    /// `EPPP NSSS` of $4001/$4005
+    pub fn write(&mut self, value: u8) {
+        self.enabled = value & 0b1000_0000 > 0;
+        self.period = (value >> 4) & 0b111;
+        self.negate = value & 0b0000_1000 > 0;
+        self.shift = value & 0b111;
+        self.reload = true;
+    }
+
+    pub fn target_period(&self, timer_period: u16) -> u16 {
+        let change = timer_period >> self.shift;
+
+        if !self.negate {
+            return timer_period + change;
+        }
+
+        if self.ones_complement {
+            return timer_period.saturating_sub(change + 1);
+        }
+
+        return timer_period.saturating_sub(change);
+    }
+
+    // the target is checked all the time, even with the sweep disabled
+    pub fn mutes(&self, timer_period: u16) -> bool {
+        return timer_period < MIN_TIMER_PERIOD || self.target_period(timer_period) > MAX_TIMER_PERIOD;
+    }
+
+    /// Half frame clock, returns the new timer period
+    pub fn clock(&mut self, timer_period: u16) -> u16 {
+        let mut timer_period = timer_period;
+
+        if self.divider == 0 && self.enabled && self.shift > 0 && !self.mutes(timer_period) {
+            timer_period = self.target_period(timer_period);
+        }
+
+        if self.divider == 0 || self.reload {
+            self.divider = self.period;
+            self.reload = false;
+        } else {
+            self.divider -= 1;
+        }
+
+        return timer_period;
+    }
+}
+
+/// Square wave channel at $4000-$4003 or $4004-$4007
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pulse {
+    pub envelope: Envelope,
+    pub sweep: Sweep,
+    pub length_counter: LengthCounter,
+    pub duty: u8,
+    pub timer_period: u16,
+    timer: u16,
+    sequence_step: u8,
+}
+
+impl Pulse {
+    pub fn new(ones_complement: bool) -> Self {
+        return Pulse {
+            envelope: Envelope::new(),
+            sweep: Sweep::new(ones_complement),
+            length_counter: LengthCounter::new(),
+            duty: 0,
+            timer_period: 0,
+            timer: 0,
+            sequence_step: 0,
+        };
+    }
+
+    /// `register` is the address offset inside the channel, 0 to 3
+    pub fn write_register(&mut self, register: usize, value: u8) {
+        match register {
+            0 => {
+                self.duty = value >> 6;
+                self.length_counter.halt = value & 0b0010_0000 > 0;
+                self.envelope.write(value);
+            },
+            1 => {
+                self.sweep.write(value);
+            },
+            2 => {
+                self.timer_period = (self.timer_period & 0x700) | value as u16;
+            },
+            3 => {
+                self.timer_period = (self.timer_period & 0x0FF) | ((value as u16 & 0b111) << 8);
+                self.length_counter.load(value >> 3);
+                self.envelope.start = true;
+                self.sequence_step = 0;
+            },
+            _ => unreachable!("pulse register {}", register),
+        }
+    }
+
+    /// Clocked every other cpu cycle
+    pub fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_period;
+            self.sequence_step = (self.sequence_step + 1) & 0b111;
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    pub fn clock_quarter_frame(&mut self) {
+        self.envelope.clock();
+    }
+
+    pub fn clock_half_frame(&mut self) {
+        self.length_counter.clock();
+        self.timer_period = self.sweep.clock(self.timer_period);
+    }
+
+    /// Current volume, 0 to 15
+    pub fn output(&self) -> u8 {
+        if !self.length_counter.is_active()
+            || self.sweep.mutes(self.timer_period)
+            || DUTY_SEQUENCES[self.duty as usize][self.sequence_step as usize] == 0 {
+            return 0;
+        }
+
+        return self.envelope.output();
+    }
+}
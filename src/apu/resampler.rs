@@ -0,0 +1,118 @@
+use std::f64::consts::PI;
+
+// kernel width in output samples and the number of sub-sample positions it is tabulated for
+const TAPS: usize = 16;
+const PHASES: usize = 32;
+// fraction of the output rate the kernel passes, a bit under nyquist to leave room for the window
+const CUTOFF: f64 = 0.45;
+
+/// Turns the output level, which changes on cpu cycles, into samples at the output rate.
+/// Every change is drawn as a band-limited step (blip_buf style): its delta goes in through a
+/// windowed sinc impulse and the samples are the running sum, so square waves come out without
+/// aliasing. The output lags the input by about `TAPS / 2` samples
+#[derive(Debug, Clone)]
+pub struct Resampler {
+    pub clock_rate: f64,
+    pub sample_rate: u32,
+    // output samples per input clock
+    ratio: f64,
+    // position of the current clock in output samples, from the first sample not read yet
+    time: f64,
+    deltas: Vec<f32>,
+    integrator: f32,
+    kernel: Vec<[f32; TAPS]>,
+}
+
+impl Resampler {
+    pub fn new(clock_rate: f64, sample_rate: u32) -> Self {
+        return Resampler {
+            clock_rate: clock_rate,
+            sample_rate: sample_rate,
+            ratio: sample_rate as f64 / clock_rate,
+            time: 0.0,
+            deltas: vec![0.0; TAPS],
+            integrator: 0.0,
+            kernel: build_kernel(),
+        };
+    }
+
+    /// The level changes by `delta` at the current clock
+    pub fn add_delta(&mut self, delta: f32) {
+        let start = self.time as usize;
+        let phase = ((self.time - start as f64) * PHASES as f64).round() as usize;
+
+        if self.deltas.len() < start + TAPS {
+            self.deltas.resize(start + TAPS, 0.0);
+        }
+
+        for (slot, weight) in self.deltas[start..start + TAPS].iter_mut().zip(self.kernel[phase].iter()) {
+            *slot += delta * weight;
+        }
+    }
+
+    /// Scales the output rate by `factor` without touching the filters, for dynamic rate control.
+    /// Pending samples keep their positions
+    pub fn set_rate_adjustment(&mut self, factor: f64) {
+        self.ratio = self.sample_rate as f64 / self.clock_rate * factor;
+    }
+
+    /// One input clock passes
+    pub fn clock(&mut self) {
+        self.time += self.ratio;
+    }
+
+    /// Appends every sample that no later change can reach anymore
+    pub fn read_samples(&mut self, samples: &mut Vec<f32>) {
+        let count = self.time as usize;
+
+        if self.deltas.len() < count {
+            self.deltas.resize(count, 0.0);
+        }
+
+        for delta in self.deltas.drain(..count) {
+            self.integrator += delta;
+            samples.push(self.integrator);
+        }
+
+        self.time -= count as f64;
+    }
+}
+
+// one windowed sinc impulse per sub-sample phase, the last row is a whole sample late
+fn build_kernel() -> Vec<[f32; TAPS]> {
+    let half = (TAPS / 2) as f64;
+
+    return (0..=PHASES).map(|phase| {
+        let offset = phase as f64 / PHASES as f64;
+        let mut row = [0.0f64; TAPS];
+
+        for (tap, weight) in row.iter_mut().enumerate() {
+            let x = tap as f64 - (half - 1.0) - offset;
+
+            let sinc = if x == 0.0 {
+                2.0 * CUTOFF
+            } else {
+                (2.0 * PI * CUTOFF * x).sin() / (PI * x)
+            };
+            // blackman window over the kernel width
+            let position = (x + half) / TAPS as f64;
+            let window = 0.42 - 0.5 * (2.0 * PI * position).cos() + 0.08 * (4.0 * PI * position).cos();
+
+            *weight = sinc * window;
+        }
+
+        // every step has to add up to its full delta
+        let sum: f64 = row.iter().sum();
+        let mut normalized = [0.0f32; TAPS];
+        for (weight, raw) in normalized.iter_mut().zip(row.iter()) {
+            *weight = (raw / sum) as f32;
+        }
+
+        normalized
+    }).collect();
+}
+
+/// 16 bit pcm for an output sample, clipping outside -1.0..=1.0
+pub fn sample_to_i16(sample: f32) -> i16 {
+    return (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+}
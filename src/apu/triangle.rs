@@ -0,0 +1,117 @@
+use super::length_counter::LengthCounter;
+
+const TRIANGLE_SEQUENCE: [u8; 32] = [
+    15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0,
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+];
+
+// periods of 0 and 1 run the sequencer above 27 kHz, the real dac smears that into a
+// constant level. The sequencer is held instead, which sounds the same without popping
+const MIN_AUDIBLE_PERIOD: u16 = 2;
+
+/// Quarter frame counter that gates the triangle next to its length counter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LinearCounter {
+    pub counter: u8,
+    pub reload_value: u8,
+    pub reload: bool,
+    // the same bit halts the length counter
+    pub control: bool,
+}
+
+impl LinearCounter {
+    pub fn new() -> Self {
+        return LinearCounter {
+            counter: 0,
+            reload_value: 0,
+            reload: false,
+            control: false,
+        };
+    }
+
+    /// Quarter frame clock
+    pub fn clock(&mut self) {
+        if self.reload {
+            self.counter = self.reload_value;
+        } else if self.counter > 0 {
+            self.counter -= 1;
+        }
+
+        if !self.control {
+            self.reload = false;
+        }
+    }
+}
+
+/// Triangle wave channel at $4008-$400B, no volume control
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Triangle {
+    pub linear_counter: LinearCounter,
+    pub length_counter: LengthCounter,
+    pub timer_period: u16,
+    timer: u16,
+    sequence_step: u8,
+}
+
+impl Triangle {
+    pub fn new() -> Self {
+        return Triangle {
+            linear_counter: LinearCounter::new(),
+            length_counter: LengthCounter::new(),
+            timer_period: 0,
+            timer: 0,
+            sequence_step: 0,
+        };
+    }
+
+    /// `register` is the address offset inside the channel, 0 to 3
+    pub fn write_register(&mut self, register: usize, value: u8) {
+        match register {
+            0 => {
+                self.linear_counter.control = value & 0b1000_0000 > 0;
+                self.linear_counter.reload_value = value & 0b0111_1111;
+                self.length_counter.halt = self.linear_counter.control;
+            },
+            // $4009 is not connected
+            1 => {},
+            2 => {
+                self.timer_period = (self.timer_period & 0x700) | value as u16;
+            },
+            3 => {
+                self.timer_period = (self.timer_period & 0x0FF) | ((value as u16 & 0b111) << 8);
+                self.length_counter.load(value >> 3);
+                self.linear_counter.reload = true;
+            },
+            _ => unreachable!("triangle register {}", register),
+        }
+    }
+
+    /// Clocked every cpu cycle, twice the rate of the other channels
+    pub fn clock_timer(&mut self) {
+        if self.timer > 0 {
+            self.timer -= 1;
+
+            return;
+        }
+
+        self.timer = self.timer_period;
+        if self.linear_counter.counter > 0
+            && self.length_counter.is_active()
+            && self.timer_period >= MIN_AUDIBLE_PERIOD {
+            self.sequence_step = (self.sequence_step + 1) & 0x1F;
+        }
+    }
+
+    pub fn clock_quarter_frame(&mut self) {
+        self.linear_counter.clock();
+    }
+
+    pub fn clock_half_frame(&mut self) {
+        self.length_counter.clock();
+    }
+
+    /// Current level, 0 to 15. A stopped triangle keeps its last level instead of dropping to 0
+    pub fn output(&self) -> u8 {
+        return TRIANGLE_SEQUENCE[self.sequence_step as usize];
+    }
+}
@@ -0,0 +1,93 @@
+use std::{env, fs, process::exit};
+
+use log::LevelFilter;
+
+use dendynes::{
+    cartridge::Cartridge,
+    frontend::{
+        headless::{frame_hash, run_frames, save_screenshot, HeadlessOptions, InputScript, USAGE},
+        wav::WavWriter,
+        AudioSink, NullSink,
+    },
+    logging::init_logger,
+    nes::Nes,
+};
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    exit(2);
+}
+
+fn main() {
+    init_logger(LevelFilter::Error).unwrap();
+
+    if env::args().skip(1).any(|argument| argument == "-h" || argument == "--help") {
+        print!("{}", USAGE);
+        exit(0);
+    }
+
+    let arguments = HeadlessOptions::parse(env::args().skip(1)).unwrap_or_else(|error| fail(&error.to_string()));
+
+    let mut script = match &arguments.input_script_path {
+        Some(path) => {
+            let source = fs::read_to_string(path).unwrap_or_else(|error| {
+                fail(&format!("could not read {}: {}", path.display(), error))
+            });
+
+            InputScript::parse(&source).unwrap_or_else(|error| fail(&error.to_string()))
+        },
+        None => InputScript::default(),
+    };
+
+    let cartridge = match Cartridge::from_path(&arguments.rom_path) {
+        Ok(cartridge) => cartridge,
+        Err(error) => {
+            eprintln!("{}: {}", arguments.rom_path.display(), error);
+            exit(1);
+        },
+    };
+    let mut nes = Nes::with_power_on_ram(cartridge, arguments.region, arguments.power_on_ram);
+    nes.set_sample_rate(arguments.sample_rate);
+
+    let mut wav = arguments.wav_path.as_ref().map(|path| {
+        WavWriter::create(path, arguments.sample_rate, arguments.sample_format).unwrap_or_else(|error| {
+            eprintln!("could not create {}: {}", path.display(), error);
+            exit(1);
+        })
+    });
+    let audio: &mut dyn AudioSink = match wav.as_mut() {
+        Some(wav) => wav,
+        None => &mut NullSink,
+    };
+
+    let (frames, result) = run_frames(&mut nes, arguments.frames, &mut script, &mut NullSink, audio);
+
+    // the screen is still worth a look when the cpu gave up
+    if let Some(path) = &arguments.screenshot_path {
+        if let Err(error) = save_screenshot(nes.frame_buffer(), path) {
+            eprintln!("could not write {}: {}", path.display(), error);
+            exit(1);
+        }
+    }
+
+    println!("frames: {}", frames);
+    println!("frame hash: {:016x}", frame_hash(nes.frame_buffer()));
+
+    if let (Some(wav), Some(path)) = (wav, &arguments.wav_path) {
+        let samples = wav.samples_written();
+        let hash = wav.hash();
+
+        if let Err(error) = wav.finish() {
+            eprintln!("could not write {}: {}", path.display(), error);
+            exit(1);
+        }
+
+        println!("audio samples: {}", samples);
+        println!("audio hash: {:016x}", hash);
+    }
+
+    if let Err(fault) = result {
+        eprintln!("cpu fault: {}", fault);
+        exit(3);
+    }
+}
@@ -0,0 +1,47 @@
+use bitflags::bitflags;
+
+
+bitflags! {
+    /// Every chip that can pull the shared /IRQ line low
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct IrqSource: u8 {
+        const FRAME_COUNTER = 0b0000_0001;
+        const DMC           = 0b0000_0010;
+        const MAPPER        = 0b0000_0100;
+    }
+}
+
+/// Level triggered /IRQ: it stays asserted while at least one source holds it,
+/// each source asserts and acknowledges its own bit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct IrqLine {
+    sources: IrqSource,
+}
+
+impl IrqLine {
+    pub fn new() -> Self {
+        return IrqLine {
+            sources: IrqSource::empty(),
+        };
+    }
+
+    pub fn assert(&mut self, source: IrqSource) {
+        self.sources.insert(source);
+    }
+
+    pub fn acknowledge(&mut self, source: IrqSource) {
+        self.sources.remove(source);
+    }
+
+    pub fn set(&mut self, source: IrqSource, asserted: bool) {
+        self.sources.set(source, asserted);
+    }
+
+    pub fn is_asserted(&self) -> bool {
+        return !self.sources.is_empty();
+    }
+
+    pub fn sources(&self) -> IrqSource {
+        return self.sources;
+    }
+}
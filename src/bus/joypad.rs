@@ -36,6 +36,11 @@ impl Joypad {
         return data;
     }
 
+    /// Next bit `read` would return, without shifting
+    pub fn peek(&self) -> u8 {
+        return (self.state & 0x80 > 0) as u8;
+    }
+
     pub fn write(&mut self) {
         self.state = self.buttons_pressed.bits();
     }
@@ -1,12 +1,13 @@
+pub mod irq;
 pub mod joypad;
 
 use std::{rc::Rc, cell::RefCell};
 
 use log::{warn, debug, error, trace};
 
-use self::joypad::Joypad;
+use self::{irq::{IrqLine, IrqSource}, joypad::Joypad};
 
-use super::{ppu::PPU, cartridge::Cartridge};
+use super::{apu::APU, ppu::PPU, cartridge::Cartridge, clock::MasterClock, cpu::bus::CpuBus, region::Region};
 
 const CPU_MEMORY_SIZE: usize = 0x800;
 
@@ -28,28 +29,10 @@ const PPU_ADDRESS: usize = 0x2006;
 const PPU_DATA_ADDRESS: usize = 0x2007;
 const OAM_DMA_ADDRESS: usize = 0x4014;
 
-// APU & I/O
-const SQ1_VOL_ADDRESS: usize = 0x4000;
-const SQ1_SWEEP_ADDRESS: usize = 0x4001;
-const SQ1_LO_ADDRESS: usize = 0x4002;
-const SQ1_HI_ADDRESS: usize = 0x4003;
-const SQ2_VOL_ADDRESS: usize = 0x4004;
-const SQ2_SWEEP_ADDRESS: usize = 0x4005;
-const SQ2_LO_ADDRESS: usize = 0x4006;
-const SQ2_HI_ADDRESS: usize = 0x4007;
-const TRI_LINEAR_ADDRESS: usize = 0x4008;
-const APU_UNUSED_REGISTER_1_ADDRESS: usize = 0x4009;
-const TRI_LO_ADDRESS: usize = 0x400A;
-const TRI_HI_ADDRESS: usize = 0x400B;
-const NOISE_VOL_ADDRESS: usize = 0x400C;
-const APU_UNUSED_REGISTER_2_ADDRESS: usize = 0x400D;
-const NOISE_LO_ADDRESS: usize = 0x400E;
-const NOISE_HI_ADDRESS: usize = 0x400F;
-const DMC_FREQ_ADDRESS: usize = 0x4010;
-const DMC_RAW_ADDRESS: usize = 0x4011;
-const DMC_START_ADDRESS: usize = 0x4012;
-const DMC_LEN_ADDRESS: usize = 0x4013;
-// const OAM_DMA_ADDRESS: usize = 0x4014;
+// APU & I/O, the apu decodes its own registers
+const APU_REGISTERS_START: usize = 0x4000;
+// last write-only channel register
+const APU_CHANNELS_END: usize = 0x4013;
 const SND_CHN_ADDRESS: usize = 0x4015;
 
 const JOYPAD_1_IO_ADDRESS: usize = 0x4016;
@@ -64,39 +47,112 @@ const CARTRIDGE_PAGE_END: usize = 0xFFFF;
 const PROGRAM_ROM_PAGE_START: usize = 0x8000;
 const PROGRAM_ROM_PAGE_END: usize = 0xFFFF;
 
-pub struct Bus<'a> {
+pub struct Bus {
     pub cpu_memory: [u8; CPU_MEMORY_SIZE],
     pub cpu_cycles: u64,
-    pub ppu: &'a mut PPU,
+    pub clock: MasterClock,
+    pub ppu: PPU,
+    pub apu: APU,
     pub cartridge: Rc<RefCell<Cartridge>>,
 
     pub joypads: [Joypad; 2],
+
+    pub irq: IrqLine,
 }
 
-impl<'a> Bus<'a> {
-    pub fn new(ppu_device: &'a mut PPU, cartridge: Rc<RefCell<Cartridge>>) -> Self {
+impl Bus {
+    pub fn new(ppu_device: PPU, cartridge: Rc<RefCell<Cartridge>>, region: Region) -> Self {
         return Bus {
             cpu_memory: [0; CPU_MEMORY_SIZE],
             cpu_cycles: 0u64,
+            clock: MasterClock::new(region),
             ppu: ppu_device,
+            apu: APU::new(region),
             cartridge: cartridge,
             joypads: [Joypad::new(); 2],
+            irq: IrqLine::new(),
         };
     }
 
+    /// RESET reaches the PPU and the APU too, RAM and the cartridge keep their contents
+    pub fn reset(&mut self) {
+        self.ppu.reset();
+        self.apu.reset();
+        self.sync_apu_interrupts();
+    }
+
+    fn sync_apu_interrupts(&mut self) {
+        self.irq.set(IrqSource::FRAME_COUNTER, self.apu.frame_interrupt());
+        self.irq.set(IrqSource::DMC, self.apu.dmc_interrupt());
+    }
+
+    /// Sample fetch the dmc asked for, on a cpu read cycle. The cpu cycle already ticked for the
+    /// read becomes the halt cycle, then come a dummy cycle, an alignment cycle when needed and
+    /// the fetch itself, before the cpu gets its read done
+    fn run_dmc_dma(&mut self) {
+        let address = match self.apu.dmc.dma_address() {
+            Some(address) => address,
+            None => return,
+        };
+
+        // halt, dummy and fetch
+        let mut cycles = 3;
+        self.tick(1);
+
+        // the fetch lands on a get cycle
+        if self.cpu_cycles & 1 == 0 {
+            cycles += 1;
+            self.tick(1);
+        }
+
+        self.tick(1);
+        let value = self.cartridge.borrow().cpu_read_u8(address as usize);
+        self.apu.dmc.load_sample(value);
+        self.sync_apu_interrupts();
+
+        // the cpu repeats the halted read
+        self.tick(1);
+        trace!("DMC DMA stalled the cpu for {} cycles", cycles);
+    }
+
     pub fn load_rom(&mut self) {
 
     }
 
-    pub fn poll_nmi_interrupt(&mut self) -> bool {
-        return self.ppu.nmi_interrupt;
+    pub fn read_memory_u16(&mut self, index: usize) -> u16 {
+        return u16::from_le_bytes([
+            self.read_memory_u8(index), 
+            self.read_memory_u8(index + 1)
+        ]);
     }
+    
+    pub fn write_memory_u16(&mut self, index: usize, value: u16) {
+        let [low, high] = u16::to_le_bytes(value);
 
-    pub fn nmi_handled(&mut self) {
-        self.ppu.nmi_interrupt = false;
+        self.write_memory_u8(index, low);
+        self.write_memory_u8(index + 1, high);
+    }
+
+}
+
+impl CpuBus for Bus {
+    /// Level of /NMI, the cpu does the edge detection
+    fn poll_nmi_interrupt(&self) -> bool {
+        return self.ppu.nmi_line();
     }
 
-    pub fn read_memory_u8(&mut self, index: usize) -> u8 {
+    /// Level of /IRQ, the cpu masks it with the I flag itself
+    fn poll_irq_interrupt(&self) -> bool {
+        return self.irq.is_asserted();
+    }
+
+    fn ppu_position(&self) -> Option<(i32, usize)> {
+        return Some((self.ppu.scanlines, self.ppu.cycles));
+    }
+
+    fn read_memory_u8(&mut self, index: usize) -> u8 {
+        self.run_dmc_dma();
+
         match index {
             CPU_RAM_PAGE_START..=CPU_RAM_PAGE_END => {
                 return self.cpu_memory[index & CPU_RAM_MIRROR_MASK];
@@ -123,8 +179,14 @@ impl<'a> Bus<'a> {
             IO_PAGE_START..=IO_PAGE_END => {
                 return self.read_memory_u8(index & IO_MIRROR_MASK);
             },
-            SQ1_VOL_ADDRESS..=SND_CHN_ADDRESS => {
-                warn!("Attempt to read unimplemented APU registers {:X}", index);
+            SND_CHN_ADDRESS => {
+                let status = self.apu.read_status();
+                self.sync_apu_interrupts();
+
+                return status;
+            },
+            APU_REGISTERS_START..=APU_CHANNELS_END => {
+                warn!("Attempt to read write-only APU registers {:X}", index);
 
                 return 0;
             },
@@ -156,14 +218,42 @@ impl<'a> Bus<'a> {
         }
     }
 
-    pub fn read_memory_u16(&mut self, index: usize) -> u16 {
-        return u16::from_le_bytes([
-            self.read_memory_u8(index), 
-            self.read_memory_u8(index + 1)
-        ]);
+    /// Value `read_memory_u8` would return, without any side effects: vblank, the ppu read buffer
+    /// and the joypad shift registers stay untouched
+    fn peek_u8(&self, index: usize) -> u8 {
+        match index {
+            CPU_RAM_PAGE_START..=CPU_RAM_PAGE_END => {
+                return self.cpu_memory[index & CPU_RAM_MIRROR_MASK];
+            },
+            PPU_STATUS_ADDRESS => {
+                return self.ppu.peek_status_register();
+            },
+            OAM_DATA_ADDRESS => {
+                return self.ppu.read_oam_data_register();
+            },
+            PPU_DATA_ADDRESS => {
+                return self.ppu.peek_data_register();
+            },
+            IO_PAGE_START..=IO_PAGE_END => {
+                return self.peek_u8(index & IO_MIRROR_MASK);
+            },
+            SND_CHN_ADDRESS => {
+                return self.apu.peek_status();
+            },
+            JOYPAD_1_IO_ADDRESS | JOYPAD_2_IO_ADDRESS => {
+                return self.joypads[index & 0x1].peek();
+            },
+            CARTRIDGE_PAGE_START..=usize::MAX => {
+                return self.cartridge.borrow().cpu_peek_u8(index);
+            },
+            _ => {
+                // write-only ppu registers, apu and unused io
+                return 0;
+            },
+        }
     }
 
-    pub fn write_memory_u8(&mut self, index: usize, value: u8) {
+    fn write_memory_u8(&mut self, index: usize, value: u8) {
         match index {
             CPU_RAM_PAGE_START..=CPU_RAM_PAGE_END => {
                 self.cpu_memory[index & CPU_RAM_MIRROR_MASK] = value;
@@ -187,9 +277,13 @@ impl<'a> Bus<'a> {
                 self.ppu.write_address_register(value);
             },
             OAM_DMA_ADDRESS => {
-                let mut cycles = 0;
                 let start_address = (value as u16) << 8;
                 let end_address = ((value as u16) << 8) | 0x00FF; 
+
+                // the write itself was ticked by the cpu, dma halts it for one more cycle
+                // and aligns to a read cycle before copying
+                self.tick(1);
+                let mut cycles = 1;
                 
                 if self.cpu_cycles % 2 == 1 {
                     cycles += 1;
@@ -197,6 +291,17 @@ impl<'a> Bus<'a> {
                 }
 
                 for address in start_address..=end_address {
+                    // a dmc fetch takes the get cycle of this byte, the copy goes on after
+                    // one more alignment cycle
+                    if let Some(sample_address) = self.apu.dmc.dma_address() {
+                        let sample = self.cartridge.borrow().cpu_read_u8(sample_address as usize);
+                        self.apu.dmc.load_sample(sample);
+                        self.sync_apu_interrupts();
+
+                        self.tick(2);
+                        cycles += 2;
+                    }
+
                     let value = self.read_memory_u8(address as usize);
                     
                     self.tick(1);
@@ -218,16 +323,16 @@ impl<'a> Bus<'a> {
             IO_PAGE_START..=IO_PAGE_END => {
                 // return self.read_memory_u8(index & IO_MIRROR_MASK);
             },
-            SQ1_VOL_ADDRESS..=SND_CHN_ADDRESS => {
-                warn!("Attempt to read unimplemented APU registers {:X}", index);
-
-                // return 0;
+            APU_REGISTERS_START..=SND_CHN_ADDRESS | JOYPAD_2_IO_ADDRESS => {
+                // $4017 is the apu frame counter on writes
+                self.apu.write_register(index, value);
+                self.sync_apu_interrupts();
             },
-            JOYPAD_1_IO_ADDRESS | JOYPAD_2_IO_ADDRESS => {
-                warn!("Attempt to read joypad registers {:X}", index);
-                let joypad_index = (index & 0x1) as usize;
-                
-                self.joypads[joypad_index].write();
+            JOYPAD_1_IO_ADDRESS => {
+                // the strobe line goes to both controller ports
+                for joypad in self.joypads.iter_mut() {
+                    joypad.write();
+                }
             },
             APU_IO_UNUSED_PAGE_START..=APU_IO_UNUSED_PAGE_END => {
                 warn!("Attempt to write to unused APU/IO memory {:X}; value={:X}", index, value);
@@ -245,29 +350,37 @@ impl<'a> Bus<'a> {
             }
         }
     }
-    
-    pub fn write_memory_u16(&mut self, index: usize, value: u16) {
-        let [low, high] = u16::to_le_bytes(value);
 
-        self.write_memory_u8(index, low);
-        self.write_memory_u8(index + 1, high);
-    }
-
-    pub fn tick(&mut self, cycles: u64) {
+    fn tick(&mut self, cycles: u64) {
         // if self.cpu_cycles == 134217730 || self.cpu_cycles == 134217730 {
         //     error!("Received zero cycles! {}; {}; {}", self.cpu_cycles, self.ppu.total_cycles, cycles);
         // }
         // if cycles.round() as usize == 0  || cycles as usize == 0{
         //     error!("Received zero cycles! {}; {}; {}", self.cpu_cycles, self.ppu.total_cycles, cycles)
         // }
-        self.cpu_cycles += cycles as u64;
-        // thrice the speed of cpu
-        for _ in 0..(cycles * 3) {
-            self.ppu.tick();
+        for _ in 0..cycles {
+            self.cpu_cycles += 1;
+
+            let ticks = self.clock.cpu_cycle();
+
+            for _ in 0..ticks.ppu {
+                self.ppu.tick();
+            }
+            for _ in 0..ticks.apu {
+                self.apu.tick();
+            }
+            for _ in 0..ticks.mapper {
+                self.cartridge.borrow_mut().mapper.clock();
+            }
+
+            // the mapper can raise its irq from clock or from the ppu scanline counter
+            let mapper_irq = self.cartridge.borrow().mapper.irq_pending();
+            self.irq.set(IrqSource::MAPPER, mapper_irq);
+            self.sync_apu_interrupts();
         }
-        // self.ppu.tick( as usize);
-        // two times slower than cpu
-        // self.apu.tick(cycles);
     }
 
-}
\ No newline at end of file
+    fn cpu_cycles(&self) -> u64 {
+        return self.cpu_cycles;
+    }
+}
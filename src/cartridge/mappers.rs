@@ -1,5 +1,6 @@
 use super::Header;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum MapperType {
     NROM = 0,
     UxRom = 2,
@@ -23,10 +24,18 @@ pub trait Mapper {
     }
 
     fn scanline(&mut self) {}
+
+    // every cpu cycle, for mappers with cycle counting irq timers
+    fn clock(&mut self) {}
+
+    // level of the mapper's /IRQ output, the mapper clears it on its own register writes
+    fn irq_pending(&self) -> bool {
+        return false;
+    }
 }
 
 mod nrom_mapper {
-    use crate::dendynes::cartridge::Header;
+    use crate::cartridge::Header;
 
     pub const FIRST_PAGE_START: usize = 0x8000;
     pub const BANK_PAGE_SIZE: usize = 0x4000;
@@ -48,7 +57,7 @@ mod nrom_mapper {
 }
 
 mod uxrom_mapper {
-    use crate::dendynes::cartridge::Header;
+    use crate::cartridge::Header;
 
     pub const FIRST_ADDRESS_RANGE_START: usize = 0x8000;
     pub const FIRST_ADDRESS_RANGE_END: usize = 0xBFFF;
@@ -0,0 +1,296 @@
+pub mod mappers;
+
+use std::{fmt, io, path::Path};
+
+use bitflags::bitflags;
+use log::{debug, info, error};
+use nom::{bytes::complete::take, IResult, number::complete::be_u8};
+
+use self::mappers::{Mapper, MapperType, new_mapper_by_type};
+
+
+#[derive(Debug)]
+pub enum RomError {
+    Io(io::Error),
+    BadMagic,
+    Truncated { expected: usize, got: usize },
+    MissingPrgRom,
+    UnsupportedMapper(u16),
+    Nes2Unsupported,
+}
+
+impl fmt::Display for RomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            RomError::Io(error) => write!(f, "could not read ROM: {}", error),
+            RomError::BadMagic => write!(f, "not an iNES ROM: missing NES<EOF> tag"),
+            RomError::Truncated { expected, got } => {
+                write!(f, "ROM is truncated: expected {} bytes, got {}", expected, got)
+            },
+            RomError::MissingPrgRom => write!(f, "ROM has no PRG banks"),
+            RomError::UnsupportedMapper(mapper) => write!(f, "mapper {} is not supported", mapper),
+            RomError::Nes2Unsupported => write!(f, "NES 2.0 ROMs are not supported"),
+        };
+    }
+}
+
+impl std::error::Error for RomError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        return match self {
+            RomError::Io(error) => Some(error),
+            _ => None,
+        };
+    }
+}
+
+impl From<io::Error> for RomError {
+    fn from(error: io::Error) -> Self {
+        return RomError::Io(error);
+    }
+}
+
+impl TryFrom<u8> for MapperType {
+    type Error = RomError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        return match value {
+            0 => Ok(MapperType::NROM),
+            2 | 32 => Ok(MapperType::UxRom),
+            _ => Err(RomError::UnsupportedMapper(value as u16)),
+        };
+    }
+}
+
+impl Into<u8> for MapperType {
+    fn into(self) -> u8 {
+        return match self {
+            MapperType::NROM => 0,
+            MapperType::UxRom => 2
+        };
+    }
+}
+
+const HEADER_SIZE: usize = 16;
+const NES_MAGIC: [u8; HEADER_NAME_SIZE] = *b"NES\x1A";
+const HEADER_NAME_SIZE: usize = 4;
+const HEADER_UNUSED_PADDING: usize = 4;
+
+const PRG_BANK_SIZE: usize = 0x4000;
+const CHR_BANK_SIZE: usize = 0x2000;
+const DETECT_NES2_FORMAT_MASK: u8 = 0b1100;
+const NES2_FORMAT: u8 = 0b1000;
+
+const BIT_FLAGS_MASK: u8 = 0b1111;
+const TRAINER_SIZE: usize = 512;
+
+
+bitflags! {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct CartridgeMapperFlags: u8 {
+        const MIRRORING   = 0b0001;
+        const BATTERY     = 0b0010;
+        const TRAINER     = 0b0100;
+        const FOUR_SCREEN = 0b1000;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mirroring {
+    Horizontal,
+    Vertical,
+    OneScreenLow,
+    OneScreenHigh,
+    FourScreen,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Header {
+    pub name: [u8; HEADER_NAME_SIZE],
+    pub prg_banks_count: u8,
+    pub chr_banks_count: u8,
+    pub mapper_flags: CartridgeMapperFlags,
+    pub prg_ram_size: u8,
+    pub mapper_id: u8,
+    pub mirroring: Mirroring,
+}
+
+
+pub struct Cartridge {
+    pub header: Header,
+
+    pub prg_memory: Vec<u8>,
+    pub chr_memory: Vec<u8>,
+    
+    pub mapper_type: MapperType,
+    
+    pub prg_banks_count: u8,
+    pub chr_banks_count: u8,
+    pub mapper: Box<dyn Mapper>,
+    pub mirroring: Mirroring,
+}
+
+impl Cartridge {
+    fn read_header(data: &[u8]) -> Result<Header, RomError> {
+        if data.len() < HEADER_SIZE {
+            return Err(RomError::Truncated { expected: HEADER_SIZE, got: data.len() });
+        }
+
+        if data[..HEADER_NAME_SIZE] != NES_MAGIC {
+            return Err(RomError::BadMagic);
+        }
+
+        if data[7] & DETECT_NES2_FORMAT_MASK == NES2_FORMAT {
+            return Err(RomError::Nes2Unsupported);
+        }
+
+        return match Self::parse_header(data) {
+            Ok((_, header)) => {
+                info!("Parsed cartridge header {:?}", header);
+                Ok(header)
+            },
+            Err(err) => {
+                error!("Could not load ROM file: failed to parse header; {}", err);
+                Err(RomError::Truncated { expected: HEADER_SIZE, got: data.len() })
+            },
+        };
+    }
+
+    fn parse_header(data: &[u8]) -> IResult<&[u8], Header> {
+        let (data, name) = take(HEADER_NAME_SIZE as u8)(data)?;
+        
+        debug!("Cartridge TAG {:?}", name);
+        
+        let (data, prg_banks_count) = be_u8(data)?;
+        let (data, chr_banks_count) = be_u8(data)?;
+        let (data, mapper_flags_byte) = be_u8(data)?;
+
+        let cartridge_mapper_flags = CartridgeMapperFlags::from_bits_truncate(
+            mapper_flags_byte & BIT_FLAGS_MASK
+        );
+
+        let mirroring: Mirroring;
+
+        if cartridge_mapper_flags.contains(CartridgeMapperFlags::FOUR_SCREEN) {
+            mirroring = Mirroring::FourScreen;
+        } else if cartridge_mapper_flags.contains(CartridgeMapperFlags::MIRRORING) {
+            mirroring = Mirroring::Vertical;
+        } else {
+            mirroring = Mirroring::Horizontal;
+        }
+
+        let (data, mapper_flags_2_byte) = be_u8(data)?;
+        let (data, prg_ram_size) = be_u8(data)?;
+        let (data, _tv_system) = be_u8(data)?;
+        let (data, _) = take(2usize)(data)?;
+        let (data, padding) = take(HEADER_UNUSED_PADDING)(data)?;
+
+        // old dumping tools wrote their name over bytes 7..16 ("DiskDude!"), flags 7 can't be trusted then
+        let mapper_flags_2_byte = if padding.iter().any(|byte| *byte != 0) {
+            debug!("Dirty header padding, ignoring flags 7");
+            0
+        } else {
+            mapper_flags_2_byte
+        };
+
+        let mapper = (mapper_flags_2_byte & 0b11110000) | (mapper_flags_byte >> 4);
+        debug!("Cartridge mapper {}", mapper);
+
+        let header = Header {
+            name: name.try_into().unwrap(),
+            prg_banks_count: prg_banks_count,
+            chr_banks_count: chr_banks_count,
+            mapper_flags: cartridge_mapper_flags,
+            prg_ram_size: prg_ram_size,
+            mapper_id: mapper,
+            mirroring: mirroring,
+        };
+
+        return Ok((data, header));
+    }
+
+    pub fn from_path<P: AsRef<Path>>(rom_dump_path: P) -> Result<Self, RomError> {
+        let data = std::fs::read(rom_dump_path.as_ref())?;
+
+        return Self::from_bytes(&data);
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, RomError> {
+        let header = Self::read_header(data)?;
+
+        if header.prg_banks_count == 0 {
+            return Err(RomError::MissingPrgRom);
+        }
+
+        let mapper_type = MapperType::try_from(header.mapper_id)?;
+
+        let mut prg_rom_start = HEADER_SIZE;
+        if header.mapper_flags.contains(CartridgeMapperFlags::TRAINER) {
+            prg_rom_start += TRAINER_SIZE;
+        }
+        let chr_rom_start = prg_rom_start + (header.prg_banks_count as usize) * PRG_BANK_SIZE;
+        let chr_rom_end = chr_rom_start + (header.chr_banks_count as usize) * CHR_BANK_SIZE;
+
+        if data.len() < chr_rom_end {
+            return Err(RomError::Truncated { expected: chr_rom_end, got: data.len() });
+        }
+
+        let prg_rom = data[prg_rom_start..chr_rom_start].to_vec();
+
+        let chr_rom = if header.chr_banks_count == 0 {
+            vec![0; PRG_BANK_SIZE]
+        } else {
+            data[chr_rom_start..chr_rom_end].to_vec()
+        };
+
+        let mapper = 
+            new_mapper_by_type(
+                mapper_type,
+                header.clone(),
+            );
+
+        let rom = Cartridge {
+            header: header,
+            prg_memory: prg_rom,
+            chr_memory: chr_rom,
+            mapper_type: mapper_type,
+            prg_banks_count: header.prg_banks_count,
+            chr_banks_count: header.chr_banks_count,
+            mapper: mapper,
+            mirroring: header.mirroring,
+        };
+
+        return Ok(rom);
+    }
+
+    pub fn cpu_read_u8(&self, index: usize) -> u8 {
+        let mapped_index = self.mapper.map_cpu_read(index);
+        debug!("Reading from cartridge rom: PC {:X}; Mapped PC {:X}", index, mapped_index);
+
+        return self.prg_memory[mapped_index];
+    }
+
+    /// Reads without notifying the mapper, for debuggers and tracing.
+    /// NROM and UxROM have no read side effects, so this is `cpu_read_u8` for now
+    pub fn cpu_peek_u8(&self, index: usize) -> u8 {
+        return self.prg_memory[self.mapper.map_cpu_read(index)];
+    }
+
+    pub fn cpu_write_u8(&mut self, index: usize, value: u8) {
+        let mapped_index = self.mapper.map_cpu_write(index, value);
+        // self.prg_memory[mapped_index] = value;
+    }
+    
+    pub fn ppu_read_u8(&self, index: usize) -> u8 {
+        let mapped_index = self.mapper.map_ppu_read(index);
+
+        return self.chr_memory[mapped_index];
+    }
+
+    pub fn ppu_write_u8(&mut self, index: usize, value: u8) {
+        let mapped_index = self.mapper.map_ppu_write(index, value);
+        if self.mapper.has_ram() {
+            self.chr_memory[mapped_index] = value;
+            // self.prg_memory[mapped_index] = value;   
+        }
+    }
+}
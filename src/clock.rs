@@ -0,0 +1,108 @@
+use crate::region::Region;
+
+pub const NTSC_MASTER_CLOCK_HZ: u32 = 21_477_272;
+pub const PAL_MASTER_CLOCK_HZ: u32 = 26_601_712;
+// Dendy runs from the same crystal as PAL
+pub const DENDY_MASTER_CLOCK_HZ: u32 = PAL_MASTER_CLOCK_HZ;
+
+/// Master clock cycles per chip cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClockDivisors {
+    pub cpu: u32,
+    pub ppu: u32,
+}
+
+impl ClockDivisors {
+    pub fn for_region(region: Region) -> Self {
+        return match region {
+            // 12:4, exactly 3 ppu dots per cpu cycle
+            Region::Ntsc => ClockDivisors { cpu: 12, ppu: 4 },
+            // 16:5, 3.2 dots per cpu cycle
+            Region::Pal => ClockDivisors { cpu: 16, ppu: 5 },
+            // 15:5, NTSC-like 3 dots per cpu cycle on the PAL crystal
+            Region::Dendy => ClockDivisors { cpu: 15, ppu: 5 },
+        };
+    }
+}
+
+/// Turns master clock cycles into ticks of one chip.
+/// Only the remainder is kept between calls, so the counter never grows past the period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockDivider {
+    pub period: u32,
+    phase: u32,
+}
+
+impl ClockDivider {
+    pub fn new(period: u32) -> Self {
+        return ClockDivider {
+            period: period,
+            phase: 0,
+        };
+    }
+
+    pub fn advance(&mut self, master_cycles: u32) -> u32 {
+        self.phase += master_cycles;
+
+        let ticks = self.phase / self.period;
+        self.phase %= self.period;
+
+        return ticks;
+    }
+}
+
+/// How many times each chip ticks during one cpu cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ClockTicks {
+    pub ppu: u32,
+    pub apu: u32,
+    pub mapper: u32,
+}
+
+/// The crystal every chip divides down from. The cpu drives emulation, so time advances
+/// one cpu cycle at a time and every other chip catches up on its own divisor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MasterClock {
+    pub region: Region,
+    pub divisors: ClockDivisors,
+    ppu: ClockDivider,
+    apu: ClockDivider,
+    mapper: ClockDivider,
+}
+
+impl MasterClock {
+    pub fn new(region: Region) -> Self {
+        let divisors = ClockDivisors::for_region(region);
+
+        return MasterClock {
+            region: region,
+            divisors: divisors,
+            ppu: ClockDivider::new(divisors.ppu),
+            // the apu and the mapper cycle counters run at the cpu rate
+            apu: ClockDivider::new(divisors.cpu),
+            mapper: ClockDivider::new(divisors.cpu),
+        };
+    }
+
+    pub fn master_clock_hz(&self) -> u32 {
+        return match self.region {
+            Region::Ntsc => NTSC_MASTER_CLOCK_HZ,
+            Region::Pal => PAL_MASTER_CLOCK_HZ,
+            Region::Dendy => DENDY_MASTER_CLOCK_HZ,
+        };
+    }
+
+    pub fn cpu_clock_hz(&self) -> f64 {
+        return self.master_clock_hz() as f64 / self.divisors.cpu as f64;
+    }
+
+    pub fn cpu_cycle(&mut self) -> ClockTicks {
+        let master_cycles = self.divisors.cpu;
+
+        return ClockTicks {
+            ppu: self.ppu.advance(master_cycles),
+            apu: self.apu.advance(master_cycles),
+            mapper: self.mapper.advance(master_cycles),
+        };
+    }
+}
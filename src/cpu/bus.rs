@@ -0,0 +1,92 @@
+pub const FLAT_MEMORY_SIZE: usize = 0x10000;
+
+/// Everything the 6502 core needs from the outside world. `Bus` wires it to the console,
+/// `FlatRam` runs the cpu on its own
+pub trait CpuBus {
+    fn read_memory_u8(&mut self, index: usize) -> u8;
+
+    fn write_memory_u8(&mut self, index: usize, value: u8);
+
+    /// Value `read_memory_u8` would return, without side effects, for tracing
+    fn peek_u8(&self, index: usize) -> u8;
+
+    fn peek_u16(&self, index: usize) -> u16 {
+        return u16::from_le_bytes([
+            self.peek_u8(index),
+            self.peek_u8((index as u16).wrapping_add(1) as usize),
+        ]);
+    }
+
+    /// Advances the rest of the system by cpu cycles
+    fn tick(&mut self, cycles: u64);
+
+    fn cpu_cycles(&self) -> u64;
+
+    /// Level of /NMI, the cpu does the edge detection
+    fn poll_nmi_interrupt(&self) -> bool;
+
+    /// Level of /IRQ, the cpu masks it with the I flag itself
+    fn poll_irq_interrupt(&self) -> bool;
+
+    /// PPU scanline and dot for the trace, buses without a PPU leave the column out
+    fn ppu_position(&self) -> Option<(i32, usize)> {
+        return None;
+    }
+}
+
+/// 64 KiB of plain RAM and nothing else: no mirrors, no devices, interrupt lines driven by hand.
+/// For conformance tests and 6502 projects that are not a NES
+pub struct FlatRam {
+    pub memory: Vec<u8>,
+    pub cycles: u64,
+    pub nmi: bool,
+    pub irq: bool,
+}
+
+impl FlatRam {
+    pub fn new() -> Self {
+        return FlatRam {
+            memory: vec![0; FLAT_MEMORY_SIZE],
+            cycles: 0,
+            nmi: false,
+            irq: false,
+        };
+    }
+
+    /// Copies `data` to `address`, wrapping around the end of memory
+    pub fn load(&mut self, address: u16, data: &[u8]) {
+        for (offset, byte) in data.iter().enumerate() {
+            self.memory[(address as usize + offset) % FLAT_MEMORY_SIZE] = *byte;
+        }
+    }
+}
+
+impl CpuBus for FlatRam {
+    fn read_memory_u8(&mut self, index: usize) -> u8 {
+        return self.memory[index % FLAT_MEMORY_SIZE];
+    }
+
+    fn write_memory_u8(&mut self, index: usize, value: u8) {
+        self.memory[index % FLAT_MEMORY_SIZE] = value;
+    }
+
+    fn peek_u8(&self, index: usize) -> u8 {
+        return self.memory[index % FLAT_MEMORY_SIZE];
+    }
+
+    fn tick(&mut self, cycles: u64) {
+        self.cycles += cycles;
+    }
+
+    fn cpu_cycles(&self) -> u64 {
+        return self.cycles;
+    }
+
+    fn poll_nmi_interrupt(&self) -> bool {
+        return self.nmi;
+    }
+
+    fn poll_irq_interrupt(&self) -> bool {
+        return self.irq;
+    }
+}
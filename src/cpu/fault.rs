@@ -0,0 +1,54 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CpuFaultKind {
+    // one of the NMOS JAM/KIL opcodes, the cpu stays halted until reset
+    Jammed,
+}
+
+/// Register file at the moment of a fault
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CpuRegisters {
+    pub program_pointer: u16,
+    pub stack_pointer: u8,
+    pub register_a: u8,
+    pub register_x: u8,
+    pub register_y: u8,
+    pub status: u8,
+}
+
+impl fmt::Display for CpuRegisters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(
+            f, "PC:{:04X} A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X}",
+            self.program_pointer, self.register_a, self.register_x, self.register_y,
+            self.status, self.stack_pointer,
+        );
+    }
+}
+
+/// Returned by `CPU::cpu_step` instead of panicking, the host decides whether to stop,
+/// reset or inspect the machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CpuFault {
+    pub kind: CpuFaultKind,
+    pub program_pointer: u16,
+    pub opcode: u8,
+    pub cycles: u64,
+    pub registers: CpuRegisters,
+}
+
+impl fmt::Display for CpuFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self.kind {
+            CpuFaultKind::Jammed => "cpu jammed by opcode",
+        };
+
+        return write!(
+            f, "{} ${:02X} at ${:04X} on cycle {} ({})",
+            description, self.opcode, self.program_pointer, self.cycles, self.registers,
+        );
+    }
+}
+
+impl std::error::Error for CpuFault {}
@@ -0,0 +1,5 @@
+pub mod bus;
+pub mod fault;
+pub mod opcode;
+pub mod processor;
+pub mod variant;
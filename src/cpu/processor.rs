@@ -1,17 +1,26 @@
 use bitflags::bitflags;
-use log::{warn, debug, info, trace, error};
-use num_traits::FromPrimitive;
+use log::{log_enabled, warn, debug, info, trace, error, Level};
 
-use crate::dendynes::{bus::Bus, memory::accessing_mode::MemoryAccessMode};
+use crate::{bus::Bus, memory::accessing_mode::MemoryAccessMode};
 
-use super::opcode::{OpcodeType, OPCODES_MAP, Opcode};
+use super::{
+    bus::CpuBus,
+    fault::{CpuFault, CpuFaultKind, CpuRegisters},
+    opcode::{OpcodeType, OPCODES, Opcode},
+    variant::CpuVariant,
+};
 
 const PROGRAM_POINTER_START: usize = 0xC000;
 const STACK_PAGE_START: usize = 0x100;
 
 const STACK_POINTER_START: usize = 0xFD;
 const RESET_PROGRAM_POINTER_ADDRESS: usize = 0xFFFC;
-const PAGE_CROSSING_CYCLES: u64 = 1;
+// reset runs the interrupt sequence with the stack writes turned into reads
+const RESET_STACK_READS: u8 = 3;
+// a jammed cpu keeps the bus busy, the rest of the console keeps running
+const JAMMED_CYCLES: u64 = 2;
+// XAA and LXA OR the accumulator with a chip dependent constant, 0xEE is the common one
+const UNSTABLE_MAGIC: u8 = 0xEE;
 
 bitflags! {
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -27,7 +36,10 @@ bitflags! {
     }
 }
 
-pub struct CPU<'a> {
+/// 6502 core, the 2A03 one unless `variant` says otherwise, generic over whatever it is wired to
+pub struct CPU<B: CpuBus = Bus> {
+    pub variant: CpuVariant,
+
     pub program_pointer: usize,
 
     pub stack_pointer: u8,
@@ -39,8 +51,23 @@ pub struct CPU<'a> {
     pub register_y: u8,
     
     pub status: StatusFlags,
+
+    // opcode that halted the cpu, only reset brings it back
+    pub jammed: Option<u8>,
+
+    // unmasked /IRQ as sampled at the end of the last cycle and of the cycle before it.
+    // The cpu polls on the penultimate cycle of an instruction, so a flag changed by
+    // CLI, SEI or PLP in their last cycle only counts after the next instruction
+    pub irq_sampled: bool,
+    pub irq_pending: bool,
+
+    // /NMI is edge triggered: the level seen on the last cycle, a rising edge latched until
+    // an interrupt sequence takes the NMI vector, and that latch one cycle late for polling
+    pub nmi_line: bool,
+    pub nmi_detected: bool,
+    pub nmi_pending: bool,
     
-    pub bus: &'a mut Bus<'a>,
+    pub bus: B,
 }
 
 mod interrupts {
@@ -60,31 +87,33 @@ mod interrupts {
     pub struct CpuInterrupt {
         pub interrupt_type: InterruptType,
         pub vector_addr: u16,
-        pub cycles: u64,
     }
 
     pub const BRK: CpuInterrupt = CpuInterrupt {
         interrupt_type: InterruptType::BRK,
         vector_addr: BRK_INTERRUPT_VECTOR_ADDRESS,
-        cycles: 1,
     };
 
     pub const IRQ: CpuInterrupt = CpuInterrupt {
         interrupt_type: InterruptType::IRQ,
         vector_addr: IRQ_INTERRUPT_VECTOR_ADDRESS,
-        cycles: (7 / 3) + 1,
     };
 
     pub const NMI: CpuInterrupt = CpuInterrupt {
         interrupt_type: InterruptType::NMI,
         vector_addr: NMI_INTERRUPT_VECTOR_ADDRESS,
-        cycles: (8 / 3) + 1,
     };
 }
 
-impl<'a> CPU<'a> {
-    pub fn new(bus: &'a mut Bus<'a>) -> Self {
+impl<B: CpuBus> CPU<B> {
+    /// 2A03, as in the NES
+    pub fn new(bus: B) -> Self {
+        return Self::with_variant(bus, CpuVariant::Ricoh2A03);
+    }
+
+    pub fn with_variant(bus: B, variant: CpuVariant) -> Self {
         let mut cpu = CPU {
+            variant: variant,
             program_pointer: PROGRAM_POINTER_START,
             stack_pointer: STACK_POINTER_START as u8,
             cycles: 0,
@@ -92,104 +121,170 @@ impl<'a> CPU<'a> {
             register_x: 0,
             register_y: 0,
             status: StatusFlags::from_bits(0x24).unwrap(),
+            jammed: None,
+            irq_sampled: false,
+            irq_pending: false,
+            nmi_line: false,
+            nmi_detected: false,
+            nmi_pending: false,
             bus: bus,
         };
-        cpu.reset();
+        cpu.power_on();
         // cpu.bus.tick(7f32);
 
         return cpu;
     }
 
-    pub fn reset(&mut self) {
-        warn!("Resetting the processor");
+    /// Registers as the chip comes up, then the reset sequence. SP starts at 0, so it ends at $FD
+    pub fn power_on(&mut self) {
         self.register_a = 0;
         self.register_x = 0;
         self.register_y = 0;
-        self.stack_pointer = STACK_POINTER_START as u8;
+        self.stack_pointer = 0;
+        self.status = StatusFlags::UNUSED;
 
-        debug!("Reading program start address from {:X}", RESET_PROGRAM_POINTER_ADDRESS);
-        self.program_pointer = self.bus.read_memory_u16(RESET_PROGRAM_POINTER_ADDRESS) as usize;
+        self.reset();
+    }
 
-        // self.program_pointer = PROGRAM_POINTER_START;
-        self.status = StatusFlags::empty();
-        self.set_unused_status();
-        // self.set_interrupt_disable_status();
+    /// The RESET line: an interrupt sequence whose pushes turn into reads. A, X, Y and the flags
+    /// other than I survive, SP goes down by 3
+    pub fn reset(&mut self) {
+        warn!("Resetting the processor");
+        self.jammed = None;
+        self.irq_sampled = false;
+        self.irq_pending = false;
+        self.nmi_detected = false;
+        self.nmi_pending = false;
+
+        // 7 cycles like any interrupt: two dummy reads, three stack reads, the vector
+        self.dummy_read(self.program_pointer);
+        self.dummy_read(self.program_pointer);
+
+        for _ in 0..RESET_STACK_READS {
+            self.dummy_read(STACK_PAGE_START + self.stack_pointer as usize);
+            self.stack_pointer = self.stack_pointer.wrapping_sub(1);
+        }
+
+        self.set_interrupt_disable_status();
+
+        debug!("Reading program start address from {:X}", RESET_PROGRAM_POINTER_ADDRESS);
+        self.program_pointer = self.read_u16(RESET_PROGRAM_POINTER_ADDRESS) as usize;
         
         trace!("CPU dump after reset: PC-{:X} | A:{:X} X:{:X} Y:{:X} P:{:X} SP:{:X};  Status: {:?}",
             self.program_pointer, self.register_a, self.register_x, self.register_y,
             self.status.bits(), self.stack_pointer, self.status
         );
-        
-        self.bus.tick(8);
     }
 
-    pub fn get_opcode_data_address(&mut self, index: usize, access_mode: MemoryAccessMode, read: bool) -> u16 {
-        let index = index as u16;
-    
+    /// One cpu cycle reading the bus. The rest of the console catches up first,
+    /// so the ppu and the mapper see the access on the cycle it happens
+    pub fn read_u8(&mut self, address: usize) -> u8 {
+        self.bus.tick(1);
+
+        let value = self.bus.read_memory_u8(address);
+        self.sample_interrupt_lines();
+
+        return value;
+    }
+
+    pub fn read_u16(&mut self, address: usize) -> u16 {
+        return u16::from_le_bytes([
+            self.read_u8(address),
+            self.read_u8((address as u16).wrapping_add(1) as usize),
+        ]);
+    }
+
+    /// One cpu cycle writing the bus
+    pub fn write_u8(&mut self, address: usize, value: u8) {
+        self.bus.tick(1);
+
+        self.bus.write_memory_u8(address, value);
+        self.sample_interrupt_lines();
+    }
+
+    fn sample_interrupt_lines(&mut self) {
+        self.irq_pending = self.irq_sampled;
+        self.irq_sampled = self.bus.poll_irq_interrupt()
+            && !self.status.contains(StatusFlags::INTERRUPT_DISABLE);
+
+        self.nmi_pending = self.nmi_detected;
+        let nmi_line = self.bus.poll_nmi_interrupt();
+        if nmi_line && !self.nmi_line {
+            self.nmi_detected = true;
+        }
+        self.nmi_line = nmi_line;
+    }
+
+    /// Read the 6502 does while it is busy with something else, the value is dropped
+    /// but the side effects (ppu status, ppu data buffer, joypads) still happen
+    pub fn dummy_read(&mut self, address: usize) {
+        self.read_u8(address);
+    }
+
+    /// Resolves the operand address, spending a cycle on every byte it reads. Indexed modes
+    /// always do the dummy read from the not yet carried address when `can_page_cross` is off
+    /// (stores and read-modify-write), and only on an actual page cross otherwise
+    pub fn get_opcode_data_address(&mut self, index: usize, access_mode: MemoryAccessMode) -> u16 {
         match access_mode {
             MemoryAccessMode::Implied => {
                 return 0;
             },
             MemoryAccessMode::Immediate => {
-                return index;
+                return index as u16;
             },
             MemoryAccessMode::Accumulator => {
                 return self.register_a as u16;
             },
             MemoryAccessMode::ZeroPage => {
-                let address = self.bus.read_memory_u8(index as usize);
-                return (address & 0xFF) as u16;
+                let address = self.read_u8(index);
+                return address as u16;
             },
             MemoryAccessMode::ZeroPageX => {
-                let address = self.bus.read_memory_u8(index as usize).wrapping_add(self.register_x);
-                return (address & 0xFF) as u16;
+                let base = self.read_u8(index);
+                self.dummy_read(base as usize);
+
+                return base.wrapping_add(self.register_x) as u16;
             },
             MemoryAccessMode::ZeroPageY => {
-                let address = self.bus.read_memory_u8(index as usize).wrapping_add(self.register_y);
-                return (address & 0xFF) as u16;
-            },
-            MemoryAccessMode::Relative(crossing_page) => {
-                let mut address = self.bus.read_memory_u8(index as usize) as u16;
+                let base = self.read_u8(index);
+                self.dummy_read(base as usize);
 
-                if (address & 0x80) == 1 {
-                    address |= 0xFF00;
-                }
+                return base.wrapping_add(self.register_y) as u16;
+            },
+            MemoryAccessMode::Relative(_) => {
+                let address = self.read_u8(index) as u16;
 
-                return address
+                return address;
             },
             MemoryAccessMode::Absolute => {
-                let address = self.bus.read_memory_u16(index as usize);
+                let address = self.read_u16(index);
                 return address;
             },
-            MemoryAccessMode::AbsoluteX(crossing_page) => {
-                let address = self.bus.read_memory_u16(index as usize);
+            MemoryAccessMode::AbsoluteX(_) => {
+                let address = self.read_u16(index);
                 let added_address = address.wrapping_add(self.register_x as u16);
 
-                if read && crossing_page {
-                    self.cross_page(address, added_address);
-                }
+                self.cross_page(address, added_address, access_mode);
 
                 return added_address;
             },
-            MemoryAccessMode::AbsoluteY(crossing_page) => {
-                let address = self.bus.read_memory_u16(index as usize);
+            MemoryAccessMode::AbsoluteY(_) => {
+                let address = self.read_u16(index);
                 let added_address = address.wrapping_add(self.register_y as u16);
 
-                if read && crossing_page {
-                    self.cross_page(address, added_address);
-                }
+                self.cross_page(address, added_address, access_mode);
 
                 return added_address;
             },
             MemoryAccessMode::Indirect => {
-                let low = self.bus.read_memory_u8(index as usize);
-                let high = self.bus.read_memory_u8((index + 1) as usize);
+                let low = self.read_u8(index);
+                let high = self.read_u8(index + 1);
                 let pointer = u16::from_le_bytes([low, high]);
 
                 if low == 0xFF {
                     let address = u16::from_le_bytes([
-                        self.bus.read_memory_u8(pointer as usize),
-                        self.bus.read_memory_u8((pointer & 0xFF00) as usize)
+                        self.read_u8(pointer as usize),
+                        self.read_u8((pointer & 0xFF00) as usize)
                     ]);
                     warn!(
                         "Bugged indirect; pointer: {:04X}; address: {:04X}",
@@ -198,53 +293,106 @@ impl<'a> CPU<'a> {
 
                     return address;
                 } else {
-                    return self.bus.read_memory_u16(pointer as usize);
+                    return self.read_u16(pointer as usize);
                 }
             },
             MemoryAccessMode::IndirectX => {
-                let pointer = self.bus.read_memory_u8(index as usize).wrapping_add(self.register_x);
+                let base = self.read_u8(index);
+                self.dummy_read(base as usize);
+
+                let pointer = base.wrapping_add(self.register_x);
                 
                 return u16::from_le_bytes([
-                    self.bus.read_memory_u8(pointer as usize),
-                    self.bus.read_memory_u8(pointer.wrapping_add(1) as usize),
+                    self.read_u8(pointer as usize),
+                    self.read_u8(pointer.wrapping_add(1) as usize),
                 ]);
             },
-            MemoryAccessMode::IndirectY(crossing_page) => {
-                let pointer = self.bus.read_memory_u8(index as usize);
+            MemoryAccessMode::IndirectY(_) => {
+                let pointer = self.read_u8(index);
                 let address = u16::from_le_bytes([
-                    self.bus.read_memory_u8(pointer as usize),
-                    self.bus.read_memory_u8(pointer.wrapping_add(1) as usize),
+                    self.read_u8(pointer as usize),
+                    self.read_u8(pointer.wrapping_add(1) as usize),
                 ]);
                 
                 let added_address = address.wrapping_add(self.register_y as u16);
                 
-                if read && crossing_page {
-                    self.cross_page(address, added_address);
-                }
+                self.cross_page(address, added_address, access_mode);
 
                 return added_address;
             },
         }
     }
 
-    pub fn cross_page(&mut self, base: u16, added: u16) {
-        if MemoryAccessMode::page_crossed(base as usize, added as usize) {
-            self.bus.tick(PAGE_CROSSING_CYCLES);
+    /// The low byte is added first, the cpu reads from the old page while it fixes the high byte
+    pub fn cross_page(&mut self, base: u16, added: u16, access_mode: MemoryAccessMode) {
+        if !access_mode.can_page_cross() || MemoryAccessMode::page_crossed(base as usize, added as usize) {
+            self.dummy_read(((base & 0xFF00) | (added & 0x00FF)) as usize);
         }
     }
 
-    pub fn read_for_trace(&mut self, index: usize, access_mode: MemoryAccessMode) -> u8 {
+    /// Effective address of the operand at `index`, resolved through `Bus::peek_u8` so nothing
+    /// is ticked or read for real. Same wrapping rules as `get_opcode_data_address`
+    pub fn peek_opcode_data_address(&self, index: usize, access_mode: MemoryAccessMode) -> u16 {
+        let argument = self.bus.peek_u8(index);
+        let zero_page_pointer = |pointer: u8| u16::from_le_bytes([
+            self.bus.peek_u8(pointer as usize),
+            self.bus.peek_u8(pointer.wrapping_add(1) as usize),
+        ]);
+
         match access_mode {
-            MemoryAccessMode::Accumulator => {
-                return self.register_a;
+            MemoryAccessMode::Implied | MemoryAccessMode::Accumulator => {
+                return 0;
             },
             MemoryAccessMode::Immediate => {
-                return self.bus.read_memory_u8(self.program_pointer);
+                return index as u16;
+            },
+            MemoryAccessMode::ZeroPage | MemoryAccessMode::Relative(_) => {
+                return argument as u16;
+            },
+            MemoryAccessMode::ZeroPageX => {
+                return argument.wrapping_add(self.register_x) as u16;
+            },
+            MemoryAccessMode::ZeroPageY => {
+                return argument.wrapping_add(self.register_y) as u16;
+            },
+            MemoryAccessMode::Absolute => {
+                return self.bus.peek_u16(index);
+            },
+            MemoryAccessMode::AbsoluteX(_) => {
+                return self.bus.peek_u16(index).wrapping_add(self.register_x as u16);
+            },
+            MemoryAccessMode::AbsoluteY(_) => {
+                return self.bus.peek_u16(index).wrapping_add(self.register_y as u16);
+            },
+            MemoryAccessMode::Indirect => {
+                let pointer = self.bus.peek_u16(index);
+                // page wrap bug of JMP ($xxFF)
+                let high_pointer = (pointer & 0xFF00) | (pointer.wrapping_add(1) & 0x00FF);
+
+                return u16::from_le_bytes([
+                    self.bus.peek_u8(pointer as usize),
+                    self.bus.peek_u8(high_pointer as usize),
+                ]);
+            },
+            MemoryAccessMode::IndirectX => {
+                return zero_page_pointer(argument.wrapping_add(self.register_x));
+            },
+            MemoryAccessMode::IndirectY(_) => {
+                return zero_page_pointer(argument).wrapping_add(self.register_y as u16);
+            },
+        }
+    }
+
+    /// Operand value as the instruction at `index` would see it, without side effects
+    pub fn read_for_trace(&self, index: usize, access_mode: MemoryAccessMode) -> u8 {
+        match access_mode {
+            MemoryAccessMode::Accumulator => {
+                return self.register_a;
             },
             _ => {
-                let address = self.get_opcode_data_address(index, access_mode, false);
+                let address = self.peek_opcode_data_address(index, access_mode);
 
-                return self.bus.read_memory_u8(address as usize);
+                return self.bus.peek_u8(address as usize);
             }
         }
     }
@@ -255,12 +403,12 @@ impl<'a> CPU<'a> {
                 return self.register_a;
             },
             MemoryAccessMode::Immediate => {
-                return self.bus.read_memory_u8(self.program_pointer);
+                return self.read_u8(self.program_pointer);
             },
             _ => {
-                let address = self.get_opcode_data_address(index, access_mode, true);
+                let address = self.get_opcode_data_address(index, access_mode);
 
-                return self.bus.read_memory_u8(address as usize);
+                return self.read_u8(address as usize);
             }
         }
     }
@@ -274,121 +422,138 @@ impl<'a> CPU<'a> {
                 self.set_register_a(value);
             },
             MemoryAccessMode::Immediate => {
-                self.bus.write_memory_u8(self.program_pointer, value);
+                self.write_u8(self.program_pointer, value);
             },
             _ => {
-                let address = self.get_opcode_data_address(index, access_mode, false);
+                let address = self.get_opcode_data_address(index, access_mode);
 
-                self.bus.write_memory_u8(address as usize, value);
+                self.write_u8(address as usize, value);
             }
         }
     }
 
-    pub fn run(&mut self) {
+    /// Read, write the old value back, write the result: the double write mappers see.
+    /// `operation` gets the operand and returns the value to store
+    pub fn read_modify_write<F>(&mut self, access_mode: MemoryAccessMode, operation: F) -> u8
+    where
+        F: FnOnce(&mut Self, u8) -> u8,
+    {
+        match access_mode {
+            MemoryAccessMode::Accumulator => {
+                let result = operation(self, self.register_a);
+                self.register_a = result;
+
+                return result;
+            },
+            _ => {
+                let address = self.get_opcode_data_address(self.program_pointer, access_mode) as usize;
+                let value = self.read_u8(address);
+
+                self.write_u8(address, value);
+                let result = operation(self, value);
+                self.write_u8(address, result);
+
+                return result;
+            }
+        }
+    }
+
+    pub fn run(&mut self) -> Result<(), CpuFault> {
         // self.reset();
     
         loop {
-            self.cpu_step();
+            self.cpu_step()?;
         }
     }
 
     // pub fn step_for_cycles(&mut self, cycles: usize) {
     //     let cycles = cycles as u128;
-    //     let cycles_since_start = self.bus.cpu_cycles;
+    //     let cycles_since_start = self.bus.cpu_cycles();
         
     //     let mut elapsed_cycles = 
     // }
-    pub fn cpu_step(&mut self) -> u64 {
-        if self.bus.poll_nmi_interrupt() {
+    pub fn cpu_step(&mut self) -> Result<u64, CpuFault> {
+        if let Some(opcode) = self.jammed {
+            self.bus.tick(JAMMED_CYCLES);
+
+            return Err(self.fault(CpuFaultKind::Jammed, self.program_pointer, opcode));
+        }
+
+        let cycles = self.bus.cpu_cycles();
+
+        if self.nmi_pending {
             self.interrupt(interrupts::NMI);
-            self.bus.nmi_handled();
+        } else if self.irq_pending {
+            self.interrupt(interrupts::IRQ);
         }
         
-        return self.execute_opcode();
+        self.execute_opcode()?;
+
+        return Ok(self.bus.cpu_cycles() - cycles);
     }
 
-    fn trace_state(&mut self) {
-        let opcode_raw = self.bus.read_memory_u8(self.program_pointer);
-        let opcode = match OpcodeType::from_u8(opcode_raw) {
-            Some(op) => op,
-            None => panic!("Found unknown opcode {}", opcode_raw),
+    pub fn registers(&self) -> CpuRegisters {
+        return CpuRegisters {
+            program_pointer: self.program_pointer as u16,
+            stack_pointer: self.stack_pointer,
+            register_a: self.register_a,
+            register_x: self.register_x,
+            register_y: self.register_y,
+            status: self.status.bits(),
         };
-        
-        let opcode_metadata = match OPCODES_MAP.get(&opcode) {
-            Some(op) => op,
-            None => {
-                trace!("Could not find opcode {:?} ({:02X}) in opcodes map", opcode, opcode_raw);
-                panic!("Could not find opcode {:?} ({:02X}) in opcodes map", opcode, opcode_raw);
-            }
+    }
+
+    fn fault(&self, kind: CpuFaultKind, program_pointer: usize, opcode: u8) -> CpuFault {
+        let fault = CpuFault {
+            kind: kind,
+            program_pointer: program_pointer as u16,
+            opcode: opcode,
+            cycles: self.bus.cpu_cycles(),
+            registers: self.registers(),
         };
+        debug!("CPU fault: {}", fault);
 
-        match opcode_metadata.memory_mode {
-            MemoryAccessMode::Absolute | MemoryAccessMode::AbsoluteX(_) | MemoryAccessMode::AbsoluteY(_) |
-            MemoryAccessMode::Indirect => {
-                let [low, high] = u16::to_le_bytes(self.bus.read_memory_u16(self.program_pointer + 1));
-
-                trace!(
-                    "{:04X}  {:02X} {: <02X} {: <02X}  A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} CYC:{}",
-                    self.program_pointer, opcode_raw, low, high,
-                    self.register_a, self.register_x, self.register_y,
-                    self.status.bits(), self.stack_pointer, self.bus.cpu_cycles,
-                );
-                // info!(
-                //     "{:04X}  {:02X} {: <02X} {: <02X}  A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} CYC:{}",
-                //     self.program_pointer, opcode_raw, low, high,
-                //     self.register_a, self.register_x, self.register_y,
-                //     self.status.bits(), self.stack_pointer, self.bus.cpu_cycles,
-                // );
-            },
-            MemoryAccessMode::Immediate | MemoryAccessMode::ZeroPage | MemoryAccessMode::ZeroPageX |
-            MemoryAccessMode::ZeroPageY | MemoryAccessMode::Relative(_) | MemoryAccessMode::IndirectX |
-            MemoryAccessMode::IndirectY(_) => {
-                let arg =self.bus.read_memory_u8(self.program_pointer + 1);
-
-                trace!(
-                    "{:04X}  {:02X} {: <02X}     A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} CYC:{}",
-                    self.program_pointer, opcode_raw, arg,
-                    self.register_a, self.register_x, self.register_y,
-                    self.status.bits(), self.stack_pointer, self.bus.cpu_cycles
-                );
-                // info!(
-                //     "{:04X}  {:02X} {: <02X}     A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} CYC:{}",
-                //     self.program_pointer, opcode_raw, arg,
-                //     self.register_a, self.register_x, self.register_y,
-                //     self.status.bits(), self.stack_pointer, self.bus.cpu_cycles
-                // );
-            },
-            MemoryAccessMode::Implied | MemoryAccessMode::Accumulator => {
-                trace!(
-                    "{:04X}  {:02X}        A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} CYC:{}",
-                    self.program_pointer, opcode_raw,
-                    self.register_a, self.register_x, self.register_y,
-                    self.status.bits(), self.stack_pointer, self.bus.cpu_cycles
-                );
-                // info!(
-                //     "{:04X}  {:02X}        A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} CYC:{}",
-                //     self.program_pointer, opcode_raw,
-                //     self.register_a, self.register_x, self.register_y,
-                //     self.status.bits(), self.stack_pointer, self.bus.cpu_cycles
-                // );
-            }
+        return fault;
+    }
+
+    /// State before the instruction at PC in the nestest.log layout, minus the disassembly column:
+    /// `C000  4C F5 C5  A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7`
+    pub fn trace_line(&self) -> String {
+        let opcode_raw = self.bus.peek_u8(self.program_pointer);
+        let length = OPCODES[opcode_raw as usize].length as usize;
+
+        let mut bytes = format!("{:02X}", opcode_raw);
+        for offset in 1..length {
+            let argument = self.bus.peek_u8((self.program_pointer + offset) & 0xFFFF);
+            bytes.push_str(&format!(" {:02X}", argument));
+        }
+
+        let ppu_position = match self.bus.ppu_position() {
+            Some((scanline, dot)) => format!(" PPU:{:>3},{:>3}", scanline, dot),
+            None => String::new(),
         };
+
+        return format!(
+            "{:04X}  {: <8}  A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X}{} CYC:{}",
+            self.program_pointer, bytes,
+            self.register_a, self.register_x, self.register_y,
+            self.status.bits(), self.stack_pointer, ppu_position, self.bus.cpu_cycles(),
+        );
     }
 
-    pub fn execute_opcode(&mut self) -> u64 {
+    pub fn execute_opcode(&mut self) -> Result<u64, CpuFault> {
         debug!("!! Execute opcode start !!");
         // trace!("!!!Program Pointer: {:04X}", self.program_pointer);
 
-        let cycles = self.bus.cpu_cycles;
-        
-        self.trace_state();
-        
-        let opcode = self.bus.read_memory_u8(self.program_pointer);
-        debug!("Read opcode {}", opcode);
-        let opcode = match OpcodeType::from_u8(opcode) {
-            Some(op) => op,
-            None => panic!("Found unknown opcode {}", opcode),
-        };
+        let cycles = self.bus.cpu_cycles();
+        if log_enabled!(Level::Trace) {
+            trace!("{}", self.trace_line());
+        }
+
+        let opcode_raw = self.read_u8(self.program_pointer);
+        debug!("Read opcode {}", opcode_raw);
+        let opcode_metadata = &OPCODES[opcode_raw as usize];
+        let opcode = opcode_metadata.code;
 
         debug!(
             "CPU dump: PC-{:04X} {:?} | A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X};  Status: {:?}",
@@ -398,13 +563,19 @@ impl<'a> CPU<'a> {
         debug!("Executing converted opcode {:?}; pc {:X}", opcode, self.program_pointer);
         self.program_pointer += 1;
         debug!("Advanced PC {:X}", self.program_pointer);
-        
-        let opcode_metadata = OPCODES_MAP.get(&opcode).unwrap();
 
         debug!("Metadata {:?}", opcode_metadata);
 
         let program_pointer = self.program_pointer;
 
+        // one byte instructions still read the byte after the opcode on their second cycle
+        match opcode_metadata.memory_mode {
+            MemoryAccessMode::Implied | MemoryAccessMode::Accumulator => {
+                self.dummy_read(self.program_pointer);
+            },
+            _ => {},
+        }
+
         match opcode {
             OpcodeType::Brk => {
                 self.brk();
@@ -523,7 +694,7 @@ impl<'a> CPU<'a> {
             },
             OpcodeType::Nop | OpcodeType::Nop1 | OpcodeType::Nop2 |
             OpcodeType::Nop3 | OpcodeType::Nop4 | OpcodeType::Nop5 |
-            OpcodeType::Nop6 | OpcodeType::NopHlt => {
+            OpcodeType::Nop6 => {
                 self.nop(opcode_metadata.memory_mode);
             },
             OpcodeType::OraI | OpcodeType::OraZp | OpcodeType::OraZpx |
@@ -656,11 +827,46 @@ impl<'a> CPU<'a> {
             OpcodeType::Say => {
                 self.say(opcode_metadata.memory_mode);
             },
-            _ => panic!("Unknown opcode {:?}", opcode),
+            OpcodeType::AncI | OpcodeType::Anc1I => {
+                self.anc(opcode_metadata.memory_mode);
+            },
+            OpcodeType::AlrI => {
+                self.alr(opcode_metadata.memory_mode);
+            },
+            OpcodeType::ArrI => {
+                self.arr(opcode_metadata.memory_mode);
+            },
+            OpcodeType::XaaI => {
+                self.xaa(opcode_metadata.memory_mode);
+            },
+            OpcodeType::LxaI => {
+                self.lxa(opcode_metadata.memory_mode);
+            },
+            OpcodeType::SbxI => {
+                self.sbx(opcode_metadata.memory_mode);
+            },
+            OpcodeType::LasAy => {
+                self.las(opcode_metadata.memory_mode);
+            },
+            OpcodeType::TasAy => {
+                self.tas(opcode_metadata.memory_mode);
+            },
+            OpcodeType::ShxAy => {
+                self.shx(opcode_metadata.memory_mode);
+            },
+            OpcodeType::Jam | OpcodeType::Jam1 | OpcodeType::Jam2 |
+            OpcodeType::Jam3 | OpcodeType::Jam4 | OpcodeType::Jam5 |
+            OpcodeType::Jam6 | OpcodeType::Jam7 | OpcodeType::Jam8 |
+            OpcodeType::Jam9 | OpcodeType::Jam10 | OpcodeType::Jam11 => {
+                self.program_pointer -= 1;
+                error!("CPU jammed by {:02X} at {:04X}", opcode_raw, self.program_pointer);
+                self.jammed = Some(opcode_raw);
+
+                return Err(self.fault(CpuFaultKind::Jammed, self.program_pointer, opcode_raw));
+            },
         }
 
-        self.cycles += opcode_metadata.cycles as u64;
-        self.bus.tick(opcode_metadata.cycles as u64);
+        self.cycles += self.bus.cpu_cycles() - cycles;
         
         if program_pointer != self.program_pointer {
             // trace!("There was a jump from {:X} to {:X}", program_pointer, self.program_pointer);
@@ -672,7 +878,7 @@ impl<'a> CPU<'a> {
 
         }
 
-        return self.bus.cpu_cycles - cycles;
+        return Ok(self.bus.cpu_cycles() - cycles);
         // return 0.0f32;
     }
 
@@ -795,7 +1001,7 @@ impl<'a> CPU<'a> {
     }
     
     pub fn push_stack_u8(&mut self, value: u8) {
-        self.bus.write_memory_u8(STACK_PAGE_START + self.stack_pointer as usize, value);
+        self.write_u8(STACK_PAGE_START + self.stack_pointer as usize, value);
         self.stack_pointer = self.stack_pointer.wrapping_sub(1);
     }
 
@@ -809,7 +1015,12 @@ impl<'a> CPU<'a> {
     pub fn pop_stack_u8(&mut self) -> u8 {
         self.stack_pointer = self.stack_pointer.wrapping_add(1);
 
-        return self.bus.read_memory_u8(STACK_PAGE_START + self.stack_pointer as usize);
+        return self.read_u8(STACK_PAGE_START + self.stack_pointer as usize);
+    }
+
+    /// Stack read before the pointer moves, pulls spend a cycle on it
+    fn dummy_stack_read(&mut self) {
+        self.dummy_read(STACK_PAGE_START + self.stack_pointer as usize);
     }
 
     pub fn pop_stack_u16(&mut self) -> u16 {
@@ -821,35 +1032,59 @@ impl<'a> CPU<'a> {
 
     pub fn interrupt(&mut self, interrupt: interrupts::CpuInterrupt) {
         debug!("Interrupt {:?}; pushing PC {:04X}", interrupt, self.program_pointer);
+
+        // hardware interrupts fetch the next opcode and its operand and throw both away,
+        // BRK already spent these cycles on its opcode and the padding byte
+        if interrupt.interrupt_type != interrupts::InterruptType::BRK {
+            self.dummy_read(self.program_pointer);
+            self.dummy_read(self.program_pointer);
+        }
+
         self.push_stack_u16(self.program_pointer as u16);
-                
-        if interrupt.interrupt_type == interrupts::InterruptType::BRK {
-            self.set_break_status();
+
+        // B only exists on the stack copy, the pushed I is the one before the interrupt
+        let mut status = self.status;
+        status.set(StatusFlags::BREAK, interrupt.interrupt_type == interrupts::InterruptType::BRK);
+        status.insert(StatusFlags::UNUSED);
+
+        // the vector is picked while the status is pushed: an NMI seen by then hijacks
+        // a BRK or an IRQ, which still pushes its own B flag
+        let vector = if self.nmi_detected {
+            self.nmi_detected = false;
+            interrupts::NMI.vector_addr
         } else {
-            self.clear_break_status();
-        }
+            interrupt.vector_addr
+        };
+        self.push_stack_u8(status.bits());
+
         self.set_interrupt_disable_status();
-        self.set_unused_status();
-        self.push_stack_u8(self.status.bits());
-        
-        self.bus.tick(interrupt.cycles);
 
-        self.program_pointer = self.bus.read_memory_u16(interrupt.vector_addr as usize) as usize;
+        self.program_pointer = self.read_u16(vector as usize) as usize;
 
         debug!("End Interrupt {:?}; new PC {:04X}", interrupt, self.program_pointer);
     }
 
     fn brk(&mut self) {
+        // the padding byte was read as the dummy read of an implied instruction, the I flag
+        // only masks IRQ
         self.program_pointer += 1;
-        
-        if self.status.contains(StatusFlags::INTERRUPT_DISABLE) {
-            return;
-        }
 
         self.interrupt(interrupts::BRK);
     }
 
+    fn decimal_arithmetic(&self) -> bool {
+        return self.variant.has_decimal_mode() && self.status.contains(StatusFlags::DECIMAL_MODE);
+    }
+
     fn add_to_register_a(&mut self, value: u8) {
+        if self.decimal_arithmetic() {
+            self.add_decimal_to_register_a(value);
+        } else {
+            self.add_binary_to_register_a(value);
+        }
+    }
+
+    fn add_binary_to_register_a(&mut self, value: u8) {
         let overflowing_bit = if self.status.contains(StatusFlags::CARRY) {
             1u16
         } else {
@@ -887,27 +1122,179 @@ impl<'a> CPU<'a> {
         self.set_register_a(result);
     }
 
+    /// NMOS BCD addition: Z comes from the binary sum, N and V from the sum after only the low
+    /// digit was adjusted, C from the adjusted high digit
+    fn add_decimal_to_register_a(&mut self, value: u8) {
+        let carry = self.status.contains(StatusFlags::CARRY) as u16;
+        let register_a = self.register_a as u16;
+        let value = value as u16;
+        let binary = (register_a + value + carry) as u8;
+
+        let mut low = (register_a & 0x0F) + (value & 0x0F) + carry;
+        if low >= 0x0A {
+            low = ((low + 0x06) & 0x0F) + 0x10;
+        }
+        let mut result = (register_a & 0xF0) + (value & 0xF0) + low;
+
+        self.status.set(StatusFlags::ZERO, binary == 0);
+        self.status.set(StatusFlags::NEGATIVE, result & 0x80 > 0);
+        self.status.set(StatusFlags::OVERFLOW, !(register_a ^ value) & (register_a ^ result) & 0x80 > 0);
+
+        if result >= 0xA0 {
+            result += 0x60;
+        }
+        self.status.set(StatusFlags::CARRY, result > 0xFF);
+        self.register_a = result as u8;
+    }
+
     fn sub_from_register_a(&mut self, value: u8) {
-        let value = (value as i8).wrapping_neg().wrapping_sub(1) as u8;
+        let register_a = self.register_a;
+        let borrow = !self.status.contains(StatusFlags::CARRY) as i16;
+        let inverted = (value as i8).wrapping_neg().wrapping_sub(1) as u8;
+
+        // NMOS BCD subtraction sets every flag like the binary one, only A differs
+        self.add_binary_to_register_a(inverted);
 
-        self.add_to_register_a(value);
+        if self.decimal_arithmetic() {
+            let mut low = (register_a & 0x0F) as i16 - (value & 0x0F) as i16 - borrow;
+            if low < 0 {
+                low = ((low - 0x06) & 0x0F) - 0x10;
+            }
+            let mut result = (register_a & 0xF0) as i16 - (value & 0xF0) as i16 + low;
+            if result < 0 {
+                result -= 0x60;
+            }
+
+            self.register_a = result as u8;
+        }
     }
     
     /* +unofficial */
-    fn axa(&mut self, memory_mode: MemoryAccessMode) {
-        let address = self.get_opcode_data_address(self.program_pointer, memory_mode, true);
-        let [_, high] = u16::to_le_bytes(address);
-        let result = (self.register_a & self.register_x & high).wrapping_add(1);
+    /// SHA, SHX, SHY and TAS store `value & (H + 1)`, H being the high byte of the address
+    /// before indexing. When the index crosses a page the stored value also becomes the high
+    /// byte of the address written to
+    fn unstable_store(&mut self, value: u8, memory_mode: MemoryAccessMode) {
+        let address = self.get_opcode_data_address(self.program_pointer, memory_mode);
+        let index = match memory_mode {
+            MemoryAccessMode::AbsoluteX(_) => self.register_x,
+            _ => self.register_y,
+        };
+        let base = address.wrapping_sub(index as u16);
+        let [low, _] = u16::to_le_bytes(address);
+        let [_, high] = u16::to_le_bytes(base);
+        let result = value & high.wrapping_add(1);
 
-        self.write_opcode_result(self.program_pointer, result, memory_mode);
+        let address = if MemoryAccessMode::page_crossed(base as usize, address as usize) {
+            u16::from_le_bytes([low, result])
+        } else {
+            address
+        };
+
+        self.write_u8(address as usize, result);
+    }
+
+    fn axa(&mut self, memory_mode: MemoryAccessMode) {
+        self.unstable_store(self.register_a & self.register_x, memory_mode);
     }
 
     fn say(&mut self, memory_mode: MemoryAccessMode) {
-        let address = self.get_opcode_data_address(self.program_pointer, memory_mode, true);
-        let [_, high] = u16::to_le_bytes(address);
-        let result = self.register_y & (high + 1);
+        self.unstable_store(self.register_y, memory_mode);
+    }
 
-        self.write_opcode_result(self.program_pointer, result, memory_mode);
+    fn shx(&mut self, memory_mode: MemoryAccessMode) {
+        self.unstable_store(self.register_x, memory_mode);
+    }
+
+    fn tas(&mut self, memory_mode: MemoryAccessMode) {
+        self.stack_pointer = self.register_a & self.register_x;
+        self.unstable_store(self.stack_pointer, memory_mode);
+    }
+
+    fn las(&mut self, memory_mode: MemoryAccessMode) {
+        let opcode_data = self.read_opcode_data(self.program_pointer, memory_mode);
+        let result = opcode_data & self.stack_pointer;
+
+        self.stack_pointer = result;
+        self.set_register_x(result);
+        self.set_register_a(result);
+    }
+
+    fn anc(&mut self, memory_mode: MemoryAccessMode) {
+        self.and(memory_mode);
+        self.status.set(StatusFlags::CARRY, self.status.contains(StatusFlags::NEGATIVE));
+    }
+
+    fn alr(&mut self, memory_mode: MemoryAccessMode) {
+        let opcode_data = self.read_opcode_data(self.program_pointer, memory_mode);
+        let value = self.register_a & opcode_data;
+
+        self.status.set(StatusFlags::CARRY, value & 1 == 1);
+        self.set_register_a(value >> 1);
+    }
+
+    fn arr(&mut self, memory_mode: MemoryAccessMode) {
+        let opcode_data = self.read_opcode_data(self.program_pointer, memory_mode);
+        let mut result = (self.register_a & opcode_data) >> 1;
+
+        if self.status.contains(StatusFlags::CARRY) {
+            result |= 0b1000_0000;
+        }
+
+        if self.decimal_arithmetic() {
+            self.arr_decimal(self.register_a & opcode_data, result);
+            return;
+        }
+
+        self.set_register_a(result);
+        // carry is bit 6 of the result, overflow is bit 6 xor bit 5
+        self.status.set(StatusFlags::CARRY, result & 0b0100_0000 > 0);
+        self.status.set(StatusFlags::OVERFLOW, ((result >> 6) ^ (result >> 5)) & 1 == 1);
+    }
+
+    /// NMOS ARR with D set: N, Z and V come from the rotated value, then each digit of the
+    /// AND result above 4 gets a BCD fixup and the high one decides carry
+    fn arr_decimal(&mut self, value: u8, rotated: u8) {
+        let mut result = rotated;
+
+        self.status.set(StatusFlags::NEGATIVE, result & 0b1000_0000 > 0);
+        self.status.set(StatusFlags::ZERO, result == 0);
+        self.status.set(StatusFlags::OVERFLOW, (value ^ result) & 0b0100_0000 > 0);
+
+        let low = value & 0x0F;
+        let high = value >> 4;
+        if low + (low & 1) > 5 {
+            result = (result & 0xF0) | (result.wrapping_add(0x06) & 0x0F);
+        }
+        if high + (high & 1) > 5 {
+            self.set_carry_status();
+            result = result.wrapping_add(0x60);
+        } else {
+            self.clear_carry_status();
+        }
+
+        self.register_a = result;
+    }
+
+    fn xaa(&mut self, memory_mode: MemoryAccessMode) {
+        let opcode_data = self.read_opcode_data(self.program_pointer, memory_mode);
+
+        self.set_register_a((self.register_a | UNSTABLE_MAGIC) & self.register_x & opcode_data);
+    }
+
+    fn lxa(&mut self, memory_mode: MemoryAccessMode) {
+        let opcode_data = self.read_opcode_data(self.program_pointer, memory_mode);
+        let result = (self.register_a | UNSTABLE_MAGIC) & opcode_data;
+
+        self.register_a = result;
+        self.set_register_x(result);
+    }
+
+    fn sbx(&mut self, memory_mode: MemoryAccessMode) {
+        let opcode_data = self.read_opcode_data(self.program_pointer, memory_mode);
+        let value = self.register_a & self.register_x;
+
+        self.status.set(StatusFlags::CARRY, value >= opcode_data);
+        self.set_register_x(value.wrapping_sub(opcode_data));
     }
 
     fn aso(&mut self, memory_mode: MemoryAccessMode) {
@@ -1018,12 +1405,8 @@ impl<'a> CPU<'a> {
     }
 
     fn dcm (&mut self, memory_mode: MemoryAccessMode) {
-        let opcode_data = self.read_opcode_data(self.program_pointer, memory_mode);
-        let result = opcode_data.wrapping_sub(1);
+        let result = self.read_modify_write(memory_mode, |_, value| value.wrapping_sub(1));
 
-        self.write_opcode_result(self.program_pointer, result, memory_mode);
-
-        
         if self.register_a >= result {
             self.set_carry_status();
         } else {
@@ -1043,12 +1426,9 @@ impl<'a> CPU<'a> {
     }
 
     fn dec(&mut self, memory_mode: MemoryAccessMode) {
-        let opcode_data = self.read_opcode_data(self.program_pointer, memory_mode);
-        let result = opcode_data.wrapping_sub(1);
+        let result = self.read_modify_write(memory_mode, |_, value| value.wrapping_sub(1));
 
         self.update_status(result);
-
-        self.write_opcode_result(self.program_pointer, result, memory_mode);
     }
 
     fn dex(&mut self, memory_mode: MemoryAccessMode) {
@@ -1064,13 +1444,10 @@ impl<'a> CPU<'a> {
     }
 
     fn inc(&mut self, memory_mode: MemoryAccessMode) -> u8 {
-        let opcode_data = self.read_opcode_data(self.program_pointer, memory_mode);
-        let result = opcode_data.wrapping_add(1);
+        let result = self.read_modify_write(memory_mode, |_, value| value.wrapping_add(1));
 
         self.update_status(result);
 
-        self.write_opcode_result(self.program_pointer, result, memory_mode);
-
         return result;
     }
 
@@ -1108,75 +1485,59 @@ impl<'a> CPU<'a> {
     }
 
     fn asl(&mut self, memory_mode: MemoryAccessMode) -> u8 {
-        let opcode_data = self.read_opcode_data(self.program_pointer, memory_mode);
-        let result = opcode_data.wrapping_shl(1);
-        
-        if opcode_data >> 7  == 1{
-            self.set_carry_status();
-        } else {
-            self.clear_carry_status();
-        }
+        let result = self.read_modify_write(memory_mode, |cpu, opcode_data| {
+            cpu.status.set(StatusFlags::CARRY, opcode_data >> 7 == 1);
+
+            return opcode_data.wrapping_shl(1);
+        });
 
-        self.write_opcode_result(self.program_pointer, result, memory_mode);
         self.update_status(result);
 
         return result;
     }
 
     fn lsr(&mut self, memory_mode: MemoryAccessMode) -> u8 {
-        let opcode_data = self.read_opcode_data(self.program_pointer, memory_mode);
-        let result = opcode_data >> 1;
-        
-        if opcode_data & 1 == 1 {
-            self.set_carry_status();
-        } else {
-            self.clear_carry_status();
-        }
-        
-        self.write_opcode_result(self.program_pointer, result, memory_mode);
+        let result = self.read_modify_write(memory_mode, |cpu, opcode_data| {
+            cpu.status.set(StatusFlags::CARRY, opcode_data & 1 == 1);
+
+            return opcode_data >> 1;
+        });
+
         self.update_status(result);
 
         return result;
     }
 
     fn rol(&mut self, memory_mode: MemoryAccessMode) -> u8 {
-        let opcode_data = self.read_opcode_data(self.program_pointer, memory_mode);
-        
-        let mut result = opcode_data << 1;
-        
-        if self.status.contains(StatusFlags::CARRY) {
-            result |= 1;
-        }
-        
-        if opcode_data >> 7 == 1 {
-            self.set_carry_status();
-        } else {
-            self.clear_carry_status();
-        }
-        
-        self.write_opcode_result(self.program_pointer, result, memory_mode);
-        self.update_negative_status(result);
+        let result = self.read_modify_write(memory_mode, |cpu, opcode_data| {
+            let mut result = opcode_data << 1;
+
+            if cpu.status.contains(StatusFlags::CARRY) {
+                result |= 1;
+            }
+            cpu.status.set(StatusFlags::CARRY, opcode_data >> 7 == 1);
+
+            return result;
+        });
+
+        self.update_status(result);
         
         return result;
     }
 
     fn ror(&mut self, memory_mode: MemoryAccessMode) -> u8 {
-        let opcode_data = self.read_opcode_data(self.program_pointer, memory_mode);
-        
-        let mut result = opcode_data >> 1;
-        
-        if self.status.contains(StatusFlags::CARRY) {
-            result |= 0b1000_0000;
-        }
-        
-        if opcode_data & 1 == 1 {
-            self.set_carry_status();
-        } else {
-            self.clear_carry_status();
-        }
-        
-        self.write_opcode_result(self.program_pointer, result, memory_mode);
-        self.update_negative_status(result);
+        let result = self.read_modify_write(memory_mode, |cpu, opcode_data| {
+            let mut result = opcode_data >> 1;
+
+            if cpu.status.contains(StatusFlags::CARRY) {
+                result |= 0b1000_0000;
+            }
+            cpu.status.set(StatusFlags::CARRY, opcode_data & 1 == 1);
+
+            return result;
+        });
+
+        self.update_status(result);
 
         return result;
     }
@@ -1200,6 +1561,7 @@ impl<'a> CPU<'a> {
     fn rti(&mut self, memory_mode: MemoryAccessMode) {
         info!("Returning from interrupt");
 
+        self.dummy_stack_read();
         let recovered_status = self.pop_stack_u8();
         
         debug!("Carefully recovering from interrupt; recovering status {:X}", recovered_status);
@@ -1210,15 +1572,16 @@ impl<'a> CPU<'a> {
         recovered_status.remove(StatusFlags::BREAK);
         recovered_status.insert(StatusFlags::UNUSED);
 
-        let recovered_counter = self.pop_stack_u16();
-        debug!("Carefully recovering from interrupt; recovering program counter {}", recovered_counter);
-        
         info!(
-            "Recovering from interrupt, current status/PC: {:?}; {:X}; recovered: {:?}; {:X}",
-            self.status, self.program_pointer, recovered_status, recovered_counter
+            "Recovering from interrupt, current status/PC: {:?}; {:X}; recovered status: {:?}",
+            self.status, self.program_pointer, recovered_status
         );
-
+        // restored before the pc is pulled, unlike PLP the I flag takes effect right away
         self.status = recovered_status;
+
+        let recovered_counter = self.pop_stack_u16();
+        debug!("Carefully recovering from interrupt; recovering program counter {}", recovered_counter);
+
         self.program_pointer = recovered_counter as usize;
     }
     /* -interrupts */
@@ -1226,23 +1589,23 @@ impl<'a> CPU<'a> {
     /* +branching */
 
     fn branch(&mut self, condition: bool, memory_mode: MemoryAccessMode) {
+        let displacement = self.get_opcode_data_address(
+            self.program_pointer, memory_mode
+        ) as i8;
+
         if condition {
             debug!("Branched {:?}", memory_mode);
 
-            self.bus.tick(1);
-            
-            let displacement = self.get_opcode_data_address(
-                self.program_pointer, memory_mode, true
-            ) as i8;
-
             debug!("Branching; PC {:X}", self.program_pointer);
             
             let program_pointer = (self.program_pointer as u16).wrapping_add(1);
             let jump_address = program_pointer.wrapping_add_signed(displacement as i16);
             
             debug!("Branching; New PC {:X}", jump_address);
-            
-            self.cross_page(program_pointer, jump_address);
+
+            // the taken branch reads the next opcode while it adds the offset
+            self.dummy_read(program_pointer as usize);
+            self.cross_page(program_pointer, jump_address, memory_mode);
             self.program_pointer = jump_address as usize;
         } else {
             debug!("Unsuccessfull branch");
@@ -1255,7 +1618,7 @@ impl<'a> CPU<'a> {
 
     fn jmp(&mut self, memory_mode: MemoryAccessMode) {
         let address = self.get_opcode_data_address(
-            self.program_pointer, memory_mode, true
+            self.program_pointer, memory_mode
         );
     
         debug!("Carefully jumping from {:X} to {:X}, metadata {:?}", self.program_pointer, address, memory_mode);
@@ -1266,12 +1629,10 @@ impl<'a> CPU<'a> {
     fn jsr(&mut self, opcode_metadata: &Opcode) {
         debug!("!!!JSR instruction start!!!");
         
-        let address = self.get_opcode_data_address(
-            self.program_pointer, opcode_metadata.memory_mode, true
-        );
-        
-        debug!("Got address {:X}", address);
-        
+        // the high byte of the target is fetched only after the return address is pushed
+        let low = self.read_u8(self.program_pointer);
+        self.dummy_stack_read();
+
         let saved_pointer = (self.program_pointer as u16)
             .wrapping_add((opcode_metadata.length - 1) as u16) // read memory
             .wrapping_sub(1);
@@ -1279,13 +1640,22 @@ impl<'a> CPU<'a> {
         debug!("Pushing PC to stack: actual PC {:X}; pushed PC + 2 -1 {:X}", self.program_pointer, saved_pointer);
         
         self.push_stack_u16(saved_pointer);
+
+        let high = self.read_u8(saved_pointer as usize);
+        let address = u16::from_le_bytes([low, high]);
+
+        debug!("Got address {:X}", address);
+
         self.program_pointer = address as usize; 
         
         debug!("!!!JSR instruction end!!!");
     }
 
     fn rts(&mut self, memory_mode: MemoryAccessMode) {
+        self.dummy_stack_read();
         let recovered_counter = self.pop_stack_u16();
+        // pc is incremented past the JSR operand with a read of its own
+        self.dummy_read(recovered_counter as usize);
         
         debug!("Carefully returning from subroutine; setting PC from {:X} to {:X}", self.program_pointer, recovered_counter.wrapping_add(1));
         
@@ -1363,6 +1733,7 @@ impl<'a> CPU<'a> {
     }
 
     fn pla(&mut self, memory_mode: MemoryAccessMode) {
+        self.dummy_stack_read();
         let value = self.pop_stack_u8();
         // trace!("PLA before value: {:2X}; A: {:2X}", value, self.register_a);
         self.set_register_a(value);
@@ -1378,6 +1749,7 @@ impl<'a> CPU<'a> {
     }
     
     fn plp(&mut self, memory_mode: MemoryAccessMode) {
+        self.dummy_stack_read();
         let old_status = self.pop_stack_u8();
 
         self.status = match StatusFlags::from_bits(old_status) {
@@ -0,0 +1,45 @@
+use std::{fmt, str::FromStr};
+
+/// Which 6502 the core behaves like. They share the opcode table, unofficial opcodes included,
+/// and only differ in what the D flag does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CpuVariant {
+    /// Ricoh 2A03/2A07 of the NES: an NMOS 6502 with the decimal adder cut out,
+    /// D is stored and pushed but ADC and SBC stay binary
+    #[default]
+    Ricoh2A03,
+    /// Plain NMOS 6502, ADC, SBC and their unofficial relatives work in BCD while D is set
+    Nmos6502,
+}
+
+impl CpuVariant {
+    pub fn has_decimal_mode(&self) -> bool {
+        return match self {
+            CpuVariant::Ricoh2A03 => false,
+            CpuVariant::Nmos6502 => true,
+        };
+    }
+}
+
+impl FromStr for CpuVariant {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        return match value.to_ascii_lowercase().as_str() {
+            "2a03" | "2a07" => Ok(CpuVariant::Ricoh2A03),
+            "6502" | "nmos" => Ok(CpuVariant::Nmos6502),
+            _ => Err(format!("unknown cpu variant {:?}, expected 2a03 or 6502", value)),
+        };
+    }
+}
+
+impl fmt::Display for CpuVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CpuVariant::Ricoh2A03 => "2a03",
+            CpuVariant::Nmos6502 => "6502",
+        };
+
+        return write!(f, "{}", name);
+    }
+}
@@ -1,2 +0,0 @@
-pub mod opcode;
-pub mod processor;
\ No newline at end of file
@@ -0,0 +1,87 @@
+// how far the output rate may stray from nominal, 0.5% is below what ears notice as pitch
+pub const MAX_RATE_DEVIATION: f64 = 0.005;
+
+/// Dynamic rate control. Video runs at whatever rate the window updates and the sound card at its
+/// own crystal, so a fixed sample rate slowly under- or overruns any buffer. A buffer fuller than
+/// half gets slightly fewer samples per frame, an emptier one slightly more, which holds it around
+/// half full without audible pitch changes
+pub fn rate_adjustment(fill: f32) -> f64 {
+    let fill = fill.clamp(0.0, 1.0) as f64;
+
+    return 1.0 + MAX_RATE_DEVIATION * (1.0 - 2.0 * fill);
+}
+
+/// Fixed size FIFO of samples between the emulator and an audio device callback.
+/// Pushing into a full buffer drops the newest samples. Popping waits until the buffer is half
+/// full, at the start and after every underrun, so a late frame costs one gap instead of crackling
+pub struct RingBuffer {
+    samples: Vec<f32>,
+    read: usize,
+    len: usize,
+    primed: bool,
+}
+
+impl RingBuffer {
+    pub fn new(capacity: usize) -> Self {
+        return RingBuffer {
+            samples: vec![0.0; capacity],
+            read: 0,
+            len: 0,
+            primed: false,
+        };
+    }
+
+    pub fn capacity(&self) -> usize {
+        return self.samples.len();
+    }
+
+    pub fn len(&self) -> usize {
+        return self.len;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len == 0;
+    }
+
+    /// 0.0 when empty, 1.0 when full
+    pub fn fill(&self) -> f32 {
+        return self.len as f32 / self.capacity() as f32;
+    }
+
+    /// Returns how many samples fit
+    pub fn push(&mut self, samples: &[f32]) -> usize {
+        let count = samples.len().min(self.capacity() - self.len);
+
+        for sample in samples[..count].iter() {
+            let write = (self.read + self.len) % self.capacity();
+            self.samples[write] = *sample;
+            self.len += 1;
+        }
+
+        return count;
+    }
+
+    /// Fills the front of `samples`, returns how many were available
+    pub fn pop(&mut self, samples: &mut [f32]) -> usize {
+        if !self.primed && self.len < self.capacity() / 2 {
+            return 0;
+        }
+
+        let count = samples.len().min(self.len);
+        self.primed = count == samples.len();
+
+        for sample in samples[..count].iter_mut() {
+            *sample = self.samples[self.read];
+            self.read = (self.read + 1) % self.capacity();
+            self.len -= 1;
+        }
+
+        return count;
+    }
+
+    pub fn clear(&mut self) {
+        self.read = 0;
+        self.len = 0;
+        self.primed = false;
+    }
+}
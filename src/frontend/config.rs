@@ -0,0 +1,545 @@
+use std::{env, fmt, fs, io, path::{Path, PathBuf}};
+
+use log::{info, LevelFilter};
+
+use crate::{bus::joypad::JoypadButtons, power_on::PowerOnRam, region::Region};
+
+const SETTINGS_DIRECTORY: &str = "dendynes";
+const SETTINGS_FILE_NAME: &str = "settings.cfg";
+const BINDING_KEY_PREFIX: &str = "bind.player";
+const COMMENT_START: char = '#';
+
+pub const DEFAULT_SCALE: u32 = 2;
+pub const MAX_SCALE: u32 = 8;
+pub const MAX_SAVE_STATE_SLOT: u8 = 9;
+pub const DEFAULT_RESET_KEY: &str = "F5";
+pub const DEFAULT_POWER_CYCLE_KEY: &str = "F6";
+
+pub const USAGE: &str = "\
+usage: dendynes <rom.nes> [options]
+
+options:
+    --scale <1-8>              window scale
+    --region <ntsc|pal|dendy>  console timing
+    --power-on-ram <contents>  zeros, ff, random or random:<seed>
+    --log-level <level>        off, error, warn, info, debug or trace
+    --slot <0-9>               starting save-state slot
+    --mute                     start without sound
+    --no-mute                  start with sound, even if the settings file mutes it
+    --settings <file>          settings file to use instead of the default one
+    --save-settings            remember scale, region, power-on ram, log level, slot and mute for the next runs
+";
+
+/*
+    Q - sq1
+    E - cross1
+    R - triangle1
+    T - circle1
+    1 - start1
+    share - options1
+    W - up1
+    S - down1
+    A - left1
+    D - right1
+ */
+
+// well, those keys are mapped by my external DS5 mapping tool for windows
+// totally inconvinient
+const DEFAULT_BINDINGS: [[(&str, JoypadButtons); 10]; 2] = [
+    [
+        ("Q", JoypadButtons::A),
+        ("E", JoypadButtons::B),
+        ("R", JoypadButtons::A),
+        ("T", JoypadButtons::A),
+        ("D1", JoypadButtons::START),
+        ("D2", JoypadButtons::SELECT),
+        ("W", JoypadButtons::UP),
+        ("S", JoypadButtons::DOWN),
+        ("A", JoypadButtons::LEFT),
+        ("D", JoypadButtons::RIGHT),
+    ],
+    [
+        ("U", JoypadButtons::A),
+        ("I", JoypadButtons::B),
+        ("O", JoypadButtons::A),
+        ("P", JoypadButtons::A),
+        ("D3", JoypadButtons::START),
+        ("D4", JoypadButtons::SELECT),
+        ("Up", JoypadButtons::UP),
+        ("Down", JoypadButtons::DOWN),
+        ("Left", JoypadButtons::LEFT),
+        ("Right", JoypadButtons::RIGHT),
+    ],
+];
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Usage(String),
+    Settings { path: PathBuf, line: usize, message: String },
+    Io { path: PathBuf, error: io::Error },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            ConfigError::Usage(message) => write!(f, "{}", message),
+            ConfigError::Settings { path, line, message } => {
+                write!(f, "{}:{}: {}", path.display(), line, message)
+            },
+            ConfigError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+        };
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Keyboard key, named the way the frontend names it (e.g. `Q`, `D1`, `Left`), bound to a joypad button
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub player: usize,
+    pub key: String,
+    pub button: JoypadButtons,
+}
+
+/// Everything that survives between runs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub scale: u32,
+    pub region: Region,
+    pub power_on_ram: PowerOnRam,
+    pub log_level: LevelFilter,
+    pub mute: bool,
+    // save states are not implemented yet, the slot is only remembered
+    pub save_state_slot: u8,
+    pub bindings: Vec<KeyBinding>,
+    // console buttons, named like the joypad binding keys
+    pub reset_key: String,
+    pub power_cycle_key: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let mut bindings = Vec::new();
+
+        for (player, player_bindings) in DEFAULT_BINDINGS.iter().enumerate() {
+            for (key, button) in player_bindings.iter() {
+                bindings.push(KeyBinding {
+                    player: player,
+                    key: key.to_string(),
+                    button: *button,
+                });
+            }
+        }
+
+        return Settings {
+            scale: DEFAULT_SCALE,
+            region: Region::default(),
+            power_on_ram: PowerOnRam::default(),
+            log_level: LevelFilter::Error,
+            mute: false,
+            save_state_slot: 0,
+            bindings: bindings,
+            reset_key: DEFAULT_RESET_KEY.to_string(),
+            power_cycle_key: DEFAULT_POWER_CYCLE_KEY.to_string(),
+        };
+    }
+}
+
+fn parse_scale(value: &str) -> Result<u32, String> {
+    return match value.parse::<u32>() {
+        Ok(scale @ 1..=MAX_SCALE) => Ok(scale),
+        _ => Err(format!("scale must be between 1 and {}, got {:?}", MAX_SCALE, value)),
+    };
+}
+
+fn parse_slot(value: &str) -> Result<u8, String> {
+    return match value.parse::<u8>() {
+        Ok(slot @ 0..=MAX_SAVE_STATE_SLOT) => Ok(slot),
+        _ => Err(format!("save-state slot must be between 0 and {}, got {:?}", MAX_SAVE_STATE_SLOT, value)),
+    };
+}
+
+fn parse_log_level(value: &str) -> Result<LevelFilter, String> {
+    return value.parse::<LevelFilter>().map_err(|_| format!("unknown log level {:?}", value));
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    return value.parse::<bool>().map_err(|_| format!("expected true or false, got {:?}", value));
+}
+
+fn parse_button(value: &str) -> Result<JoypadButtons, String> {
+    return JoypadButtons::from_name(&value.to_ascii_uppercase())
+        .ok_or_else(|| format!("unknown joypad button {:?}", value));
+}
+
+impl Settings {
+    /// `$XDG_CONFIG_HOME/dendynes`, `~/.config/dendynes` or `%APPDATA%\dendynes`,
+    /// falling back to the working directory
+    pub fn default_path() -> PathBuf {
+        let directory = env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+            .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")));
+
+        return match directory {
+            Some(directory) => directory.join(SETTINGS_DIRECTORY).join(SETTINGS_FILE_NAME),
+            None => PathBuf::from(SETTINGS_FILE_NAME),
+        };
+    }
+
+    /// Reads settings from `path`; a missing file is created with the defaults so it can be edited
+    pub fn load_or_create(path: &Path) -> Result<Self, ConfigError> {
+        match fs::read_to_string(path) {
+            Ok(source) => {
+                return Self::parse(&source).map_err(|(line, message)| ConfigError::Settings {
+                    path: path.to_path_buf(),
+                    line: line,
+                    message: message,
+                });
+            },
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                let settings = Settings::default();
+                info!("No settings at {}, writing defaults", path.display());
+                settings.save(path)?;
+
+                return Ok(settings);
+            },
+            Err(error) => {
+                return Err(ConfigError::Io { path: path.to_path_buf(), error: error });
+            },
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ConfigError> {
+        let io_error = |error| ConfigError::Io { path: path.to_path_buf(), error: error };
+
+        if let Some(directory) = path.parent().filter(|directory| !directory.as_os_str().is_empty()) {
+            fs::create_dir_all(directory).map_err(io_error)?;
+        }
+
+        return fs::write(path, self.to_config_string()).map_err(io_error);
+    }
+
+    /// `key = value` lines; bindings are `bind.player<1|2>.<key> = <BUTTON>`.
+    /// If the file has any binding it replaces all of the default ones
+    pub fn parse(source: &str) -> Result<Self, (usize, String)> {
+        let mut settings = Settings::default();
+        let mut bindings = Vec::new();
+
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let line = match line.find(COMMENT_START) {
+                Some(comment_start) => &line[..comment_start],
+                None => line,
+            };
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => return Err((line_number, format!("expected `key = value`, got {:?}", line.trim()))),
+            };
+
+            let result = match key {
+                "scale" => parse_scale(value).map(|scale| settings.scale = scale),
+                "region" => value.parse::<Region>().map(|region| settings.region = region),
+                "power_on_ram" => value.parse::<PowerOnRam>().map(|ram| settings.power_on_ram = ram),
+                "log_level" => parse_log_level(value).map(|level| settings.log_level = level),
+                "mute" => parse_bool(value).map(|mute| settings.mute = mute),
+                "save_state_slot" => parse_slot(value).map(|slot| settings.save_state_slot = slot),
+                "reset_key" => {
+                    settings.reset_key = value.to_string();
+                    Ok(())
+                },
+                "power_cycle_key" => {
+                    settings.power_cycle_key = value.to_string();
+                    Ok(())
+                },
+                _ if key.starts_with(BINDING_KEY_PREFIX) => {
+                    Self::parse_binding(&key[BINDING_KEY_PREFIX.len()..], value)
+                        .map(|binding| bindings.push(binding))
+                },
+                _ => Err(format!("unknown setting {:?}", key)),
+            };
+
+            result.map_err(|message| (line_number, message))?;
+        }
+
+        if !bindings.is_empty() {
+            settings.bindings = bindings;
+        }
+
+        return Ok(settings);
+    }
+
+    fn parse_binding(player_and_key: &str, button: &str) -> Result<KeyBinding, String> {
+        let (player, key) = match player_and_key.split_once('.') {
+            Some((player, key)) if !key.is_empty() => (player, key),
+            _ => return Err("bindings look like `bind.player1.Q = A`".to_string()),
+        };
+
+        let player = match player.parse::<usize>() {
+            Ok(player @ 1..=2) => player - 1,
+            _ => return Err(format!("player must be 1 or 2, got {:?}", player)),
+        };
+
+        return Ok(KeyBinding {
+            player: player,
+            key: key.to_string(),
+            button: parse_button(button)?,
+        });
+    }
+
+    pub fn to_config_string(&self) -> String {
+        let mut result = String::from("# dendynes settings\n");
+
+        result += &format!("scale = {}\n", self.scale);
+        result += &format!("region = {}\n", self.region);
+        result += &format!("power_on_ram = {}\n", self.power_on_ram);
+        result += &format!("log_level = {}\n", self.log_level.as_str().to_ascii_lowercase());
+        result += &format!("mute = {}\n", self.mute);
+        result += &format!("save_state_slot = {}\n", self.save_state_slot);
+
+        result += "\n# console buttons\n";
+        result += &format!("reset_key = {}\n", self.reset_key);
+        result += &format!("power_cycle_key = {}\n", self.power_cycle_key);
+
+        result += "\n# keyboard key = joypad button\n";
+        for binding in self.bindings.iter() {
+            let mut button_names = binding.button.iter_names().map(|(name, _)| name);
+
+            result += &format!(
+                "{}{}.{} = {}\n",
+                BINDING_KEY_PREFIX, binding.player + 1, binding.key, button_names.next().unwrap_or("")
+            );
+        }
+
+        return result;
+    }
+}
+
+/// What a run of the window frontend was asked to do: command line options on top of the settings file
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub rom_path: PathBuf,
+    pub settings_path: PathBuf,
+    pub settings: Settings,
+}
+
+impl Options {
+    /// Parses the command line (without the program name), loads the settings file and applies
+    /// the command line overrides. With `--save-settings` the result is written back.
+    pub fn load<I: IntoIterator<Item = String>>(arguments: I) -> Result<Self, ConfigError> {
+        let mut rom_path = None;
+        let mut settings_path = None;
+        let mut save_settings = false;
+
+        let mut scale = None;
+        let mut region = None;
+        let mut power_on_ram = None;
+        let mut log_level = None;
+        let mut mute = None;
+        let mut save_state_slot = None;
+
+        let mut arguments = arguments.into_iter();
+
+        while let Some(argument) = arguments.next() {
+            let mut value = |name: &str| {
+                return arguments.next()
+                    .ok_or_else(|| ConfigError::Usage(format!("{} expects a value", name)));
+            };
+
+            match argument.as_str() {
+                "--scale" => {
+                    scale = Some(parse_scale(&value("--scale")?).map_err(ConfigError::Usage)?);
+                },
+                "--region" => {
+                    region = Some(value("--region")?.parse::<Region>().map_err(ConfigError::Usage)?);
+                },
+                "--power-on-ram" => {
+                    power_on_ram = Some(value("--power-on-ram")?.parse::<PowerOnRam>().map_err(ConfigError::Usage)?);
+                },
+                "--log-level" => {
+                    log_level = Some(parse_log_level(&value("--log-level")?).map_err(ConfigError::Usage)?);
+                },
+                "--slot" => {
+                    save_state_slot = Some(parse_slot(&value("--slot")?).map_err(ConfigError::Usage)?);
+                },
+                "--mute" => {
+                    mute = Some(true);
+                },
+                "--no-mute" => {
+                    mute = Some(false);
+                },
+                "--settings" => {
+                    settings_path = Some(PathBuf::from(value("--settings")?));
+                },
+                "--save-settings" => {
+                    save_settings = true;
+                },
+                _ if argument.starts_with("--") => {
+                    return Err(ConfigError::Usage(format!("unknown option {}", argument)));
+                },
+                _ => {
+                    if rom_path.replace(PathBuf::from(&argument)).is_some() {
+                        return Err(ConfigError::Usage("only one ROM can be given".to_string()));
+                    }
+                },
+            }
+        }
+
+        let rom_path = rom_path.ok_or_else(|| ConfigError::Usage("missing ROM path".to_string()))?;
+        let settings_path = settings_path.unwrap_or_else(Settings::default_path);
+
+        let mut settings = Settings::load_or_create(&settings_path)?;
+        settings.scale = scale.unwrap_or(settings.scale);
+        settings.region = region.unwrap_or(settings.region);
+        settings.power_on_ram = power_on_ram.unwrap_or(settings.power_on_ram);
+        settings.log_level = log_level.unwrap_or(settings.log_level);
+        settings.mute = mute.unwrap_or(settings.mute);
+        settings.save_state_slot = save_state_slot.unwrap_or(settings.save_state_slot);
+
+        if save_settings {
+            settings.save(&settings_path)?;
+        }
+
+        return Ok(Options {
+            rom_path: rom_path,
+            settings_path: settings_path,
+            settings: settings,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a settings file only this test uses, removed again on drop
+    struct TempSettings(PathBuf);
+
+    impl TempSettings {
+        fn new(name: &str, contents: &str) -> Self {
+            let path = env::temp_dir().join(format!("dendynes-{}-{}.cfg", std::process::id(), name));
+            fs::write(&path, contents).unwrap();
+
+            return TempSettings(path);
+        }
+
+        fn load(&self, arguments: &[&str]) -> Result<Options, ConfigError> {
+            let mut all = vec!["game.nes".to_string(), "--settings".to_string(), self.0.display().to_string()];
+            all.extend(arguments.iter().map(|argument| argument.to_string()));
+
+            return Options::load(all);
+        }
+    }
+
+    impl Drop for TempSettings {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn config_string_round_trips() {
+        let settings = Settings {
+            scale: 4,
+            region: Region::Dendy,
+            power_on_ram: PowerOnRam::Random(Some(7)),
+            log_level: LevelFilter::Debug,
+            mute: true,
+            save_state_slot: 7,
+            bindings: vec![
+                KeyBinding { player: 0, key: "Z".to_string(), button: JoypadButtons::B },
+                KeyBinding { player: 1, key: "NumPad0".to_string(), button: JoypadButtons::SELECT },
+            ],
+            reset_key: "F1".to_string(),
+            power_cycle_key: "F2".to_string(),
+        };
+
+        assert_eq!(Settings::parse(&settings.to_config_string()), Ok(settings));
+        assert_eq!(Settings::parse(&Settings::default().to_config_string()), Ok(Settings::default()));
+    }
+
+    #[test]
+    fn bindings_replace_the_defaults() {
+        let settings = Settings::parse("bind.player2.K = start # comment\n").unwrap();
+
+        assert_eq!(settings.bindings, vec![KeyBinding { player: 1, key: "K".to_string(), button: JoypadButtons::START }]);
+        assert!(Settings::parse("bind.player3.K = A").unwrap_err().1.contains("player must be 1 or 2"));
+        assert!(Settings::parse("bind.player1.K = TURBO").unwrap_err().1.contains("unknown joypad button"));
+    }
+
+    #[test]
+    fn errors_carry_the_line_number() {
+        let source = "# comment\nscale = 3\n\nregion = mars\n";
+        assert_eq!(Settings::parse(source).unwrap_err().0, 4);
+        assert_eq!(Settings::parse("mute = true\nscale 3\n").unwrap_err().0, 2);
+
+        let (line, message) = Settings::parse("scale = 2\ncolour = blue\n").unwrap_err();
+        assert_eq!(line, 2);
+        assert!(message.contains("unknown setting \"colour\""), "{}", message);
+
+        let file = TempSettings::new("bad-line", "scale = 2\nmute = maybe\n");
+        match Settings::load_or_create(&file.0) {
+            Err(ConfigError::Settings { path, line, .. }) => {
+                assert_eq!(path, file.0);
+                assert_eq!(line, 2);
+            },
+            other => panic!("expected a settings error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn bad_command_lines_are_usage_errors() {
+        let file = TempSettings::new("usage", "");
+
+        for arguments in [&["--scale", "9"][..], &["--scale", "0"], &["--scale"], &["--slot", "10"], &["--slot", "-1"], &["other.nes"]] {
+            assert!(matches!(file.load(arguments), Err(ConfigError::Usage(_))), "{:?}", arguments);
+        }
+        assert!(matches!(Options::load(vec!["--mute".to_string()]), Err(ConfigError::Usage(_))));
+    }
+
+    #[test]
+    fn command_line_overrides_the_file() {
+        let file = TempSettings::new("overrides", "scale = 3\nregion = pal\nmute = true\n");
+
+        let options = file.load(&["--scale", "5", "--no-mute"]).unwrap();
+        assert_eq!(options.rom_path, PathBuf::from("game.nes"));
+        assert_eq!(options.settings.scale, 5);
+        assert_eq!(options.settings.region, Region::Pal);
+        assert!(!options.settings.mute);
+
+        let options = file.load(&[]).unwrap();
+        assert_eq!(options.settings.scale, 3);
+        assert!(options.settings.mute);
+
+        // the file is only written back when asked to
+        file.load(&["--region", "dendy", "--no-mute", "--save-settings"]).unwrap();
+        let saved = Settings::load_or_create(&file.0).unwrap();
+        assert_eq!(saved.region, Region::Dendy);
+        assert_eq!(saved.scale, 3);
+        assert!(!saved.mute);
+    }
+
+    #[test]
+    fn mute_flag_overrides_an_unmuted_file() {
+        let file = TempSettings::new("mute", "mute = false\n");
+
+        assert!(file.load(&["--mute"]).unwrap().settings.mute);
+        assert!(!file.load(&[]).unwrap().settings.mute);
+    }
+
+    #[test]
+    fn save_state_slot_survives_the_settings_file() {
+        let file = TempSettings::new("slot", "save_state_slot = 4\n");
+        assert_eq!(file.load(&[]).unwrap().settings.save_state_slot, 4);
+        assert_eq!(file.load(&["--slot", "9"]).unwrap().settings.save_state_slot, 9);
+
+        file.load(&["--slot", "2", "--save-settings"]).unwrap();
+        assert_eq!(Settings::load_or_create(&file.0).unwrap().save_state_slot, 2);
+
+        assert!(Settings::parse("save_state_slot = 10").unwrap_err().1.contains("between 0 and 9"));
+    }
+}
@@ -0,0 +1,289 @@
+use std::{fmt, path::{Path, PathBuf}};
+
+use ::image::ImageResult;
+use log::{debug, info};
+
+use crate::{
+    apu::DEFAULT_SAMPLE_RATE,
+    bus::joypad::{Joypad, JoypadButtons},
+    cpu::fault::CpuFault,
+    nes::{FrameBuffer, Nes},
+    power_on::PowerOnRam,
+    region::Region,
+};
+use super::{config::ConfigError, frame_to_image, step_frame, wav::SampleFormat, AudioSink, InputSource, VideoSink};
+
+pub(crate) const FNV_OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+pub(crate) const FNV_PRIME: u64 = 0x0000_0100_0000_01B3;
+
+pub const DEFAULT_FRAMES: u32 = 60;
+
+pub const USAGE: &str = "\
+usage: dendynes-headless <rom.nes> [options]
+
+options:
+    --frames <n>         frames to run (default 60)
+    --region <name>      ntsc (default), pal or dendy
+    --power-on-ram <ram> zeros (default), ff, random or random:<seed>
+    --input <script>     joypad timeline, lines of `<frame> <player> <BUTTON+BUTTON|->`
+    --screenshot <png>   write the last frame as a PNG
+    --wav <path>         record the audio as a mono WAV file
+    --sample-rate <hz>   audio output rate (default 44100)
+    --sample-format <f>  i16 (default) or f32 samples in the WAV file
+
+exits with 3 when the cpu jams on a JAM opcode, the screenshot, the WAV file and the hashes are still written
+";
+
+const NO_BUTTONS: &str = "-";
+const BUTTONS_SEPARATOR: char = '+';
+const COMMENT_START: char = '#';
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputScriptError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for InputScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "input script line {}: {}", self.line, self.message);
+    }
+}
+
+impl std::error::Error for InputScriptError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputEvent {
+    pub frame: u32,
+    pub player: usize,
+    pub buttons: JoypadButtons,
+}
+
+/// Joypad timeline, one event per line: `<frame> <player> <BUTTON+BUTTON|->`.
+/// Pressed buttons are held until the next event for the same player, `-` releases everything.
+///
+/// ```text
+/// # press start on the title screen
+/// 60  1 START
+/// 62  1 -
+/// 120 1 A+RIGHT
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InputScript {
+    pub events: Vec<InputEvent>,
+}
+
+impl InputScript {
+    pub fn parse(source: &str) -> Result<Self, InputScriptError> {
+        let mut events = Vec::new();
+
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let line = match line.find(COMMENT_START) {
+                Some(comment_start) => &line[..comment_start],
+                None => line,
+            };
+            let mut columns = line.split_whitespace();
+
+            let frame = match columns.next() {
+                Some(frame) => frame,
+                None => continue,
+            };
+            let frame = frame.parse::<u32>().map_err(|_| InputScriptError {
+                line: line_number,
+                message: format!("bad frame number {:?}", frame),
+            })?;
+
+            let player = match columns.next().map(|player| player.parse::<usize>()) {
+                Some(Ok(player @ 1..=2)) => player - 1,
+                _ => {
+                    return Err(InputScriptError {
+                        line: line_number,
+                        message: "player must be 1 or 2".to_string(),
+                    });
+                }
+            };
+
+            let buttons = Self::parse_buttons(columns.next().unwrap_or(NO_BUTTONS))
+                .map_err(|message| InputScriptError { line: line_number, message: message })?;
+
+            if let Some(extra) = columns.next() {
+                return Err(InputScriptError {
+                    line: line_number,
+                    message: format!("unexpected {:?}", extra),
+                });
+            }
+
+            events.push(InputEvent {
+                frame: frame,
+                player: player,
+                buttons: buttons,
+            });
+        }
+
+        events.sort_by_key(|event| event.frame);
+
+        return Ok(InputScript { events: events });
+    }
+
+    fn parse_buttons(source: &str) -> Result<JoypadButtons, String> {
+        let mut buttons = JoypadButtons::empty();
+
+        if source == NO_BUTTONS {
+            return Ok(buttons);
+        }
+
+        for name in source.split(BUTTONS_SEPARATOR) {
+            match JoypadButtons::from_name(&name.to_ascii_uppercase()) {
+                Some(button) => buttons.insert(button),
+                None => return Err(format!("unknown button {:?}", name)),
+            }
+        }
+
+        return Ok(buttons);
+    }
+
+    /// Applies every event scheduled for `frame`
+    pub fn apply(&self, frame: u32, joypads: &mut [Joypad; 2]) {
+        for event in self.events.iter().filter(|event| event.frame == frame) {
+            debug!("Frame {}: player {} holds {:?}", frame, event.player + 1, event.buttons);
+            joypads[event.player].buttons_pressed = event.buttons;
+        }
+    }
+}
+
+impl InputSource for InputScript {
+    fn poll(&mut self, frame: u32, joypads: &mut [Joypad; 2]) {
+        self.apply(frame, joypads);
+    }
+}
+
+/// What a headless run was asked to do, from the command line
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeadlessOptions {
+    pub rom_path: PathBuf,
+    pub frames: u32,
+    pub region: Region,
+    pub power_on_ram: PowerOnRam,
+    pub input_script_path: Option<PathBuf>,
+    pub screenshot_path: Option<PathBuf>,
+    pub wav_path: Option<PathBuf>,
+    pub sample_rate: u32,
+    pub sample_format: SampleFormat,
+}
+
+impl HeadlessOptions {
+    /// Parses the command line without the program name, like `config::Options::load`
+    pub fn parse<I: IntoIterator<Item = String>>(arguments: I) -> Result<Self, ConfigError> {
+        let mut rom_path = None;
+        let mut frames = DEFAULT_FRAMES;
+        let mut region = Region::default();
+        let mut power_on_ram = PowerOnRam::default();
+        let mut input_script_path = None;
+        let mut screenshot_path = None;
+        let mut wav_path = None;
+        let mut sample_rate = DEFAULT_SAMPLE_RATE;
+        let mut sample_format = SampleFormat::default();
+
+        let mut arguments = arguments.into_iter();
+
+        while let Some(argument) = arguments.next() {
+            let mut value = |name: &str| {
+                return arguments.next()
+                    .ok_or_else(|| ConfigError::Usage(format!("{} expects a value", name)));
+            };
+
+            match argument.as_str() {
+                "--frames" => {
+                    let raw = value("--frames")?;
+                    frames = raw.parse().map_err(|_| ConfigError::Usage(format!("bad frame count {:?}", raw)))?;
+                },
+                "--region" => {
+                    region = value("--region")?.parse().map_err(ConfigError::Usage)?;
+                },
+                "--power-on-ram" => {
+                    power_on_ram = value("--power-on-ram")?.parse().map_err(ConfigError::Usage)?;
+                },
+                "--input" => {
+                    input_script_path = Some(PathBuf::from(value("--input")?));
+                },
+                "--screenshot" => {
+                    screenshot_path = Some(PathBuf::from(value("--screenshot")?));
+                },
+                "--wav" => {
+                    wav_path = Some(PathBuf::from(value("--wav")?));
+                },
+                "--sample-rate" => {
+                    let raw = value("--sample-rate")?;
+                    sample_rate = match raw.parse() {
+                        Ok(rate) if rate > 0 => rate,
+                        _ => return Err(ConfigError::Usage(format!("bad sample rate {:?}", raw))),
+                    };
+                },
+                "--sample-format" => {
+                    sample_format = value("--sample-format")?.parse().map_err(ConfigError::Usage)?;
+                },
+                _ if argument.starts_with("--") => {
+                    return Err(ConfigError::Usage(format!("unknown option {}", argument)));
+                },
+                _ => {
+                    if rom_path.replace(PathBuf::from(&argument)).is_some() {
+                        return Err(ConfigError::Usage("only one ROM can be given".to_string()));
+                    }
+                },
+            }
+        }
+
+        return Ok(HeadlessOptions {
+            rom_path: rom_path.ok_or_else(|| ConfigError::Usage("missing ROM path".to_string()))?,
+            frames: frames,
+            region: region,
+            power_on_ram: power_on_ram,
+            input_script_path: input_script_path,
+            screenshot_path: screenshot_path,
+            wav_path: wav_path,
+            sample_rate: sample_rate,
+            sample_format: sample_format,
+        });
+    }
+}
+
+/// Runs `frames` frames, or up to the first cpu fault.
+/// Returns how many frames completed, the frame with the fault does not count
+pub fn run_frames(
+    nes: &mut Nes,
+    frames: u32,
+    input: &mut dyn InputSource,
+    video: &mut dyn VideoSink,
+    audio: &mut dyn AudioSink,
+) -> (u32, Result<(), CpuFault>) {
+    for frame in 0..frames {
+        if let Err(fault) = step_frame(nes, frame, input, video, audio) {
+            info!("Headless run stopped by a cpu fault after {} frames", frame);
+
+            return (frame, Err(fault));
+        }
+    }
+
+    info!("Headless run finished after {} frames", frames);
+
+    return (frames, Ok(()));
+}
+
+/// FNV-1a over the palette indices, stable between runs and platforms
+pub fn frame_hash(frame: &FrameBuffer) -> u64 {
+    let mut hash = FNV_OFFSET_BASIS;
+
+    for row in frame.iter() {
+        for pixel in row.iter() {
+            hash ^= *pixel as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    return hash;
+}
+
+pub fn save_screenshot(frame: &FrameBuffer, path: &Path) -> ImageResult<()> {
+    return frame_to_image(frame).save(path);
+}
@@ -0,0 +1,105 @@
+use std::sync::{Arc, Mutex};
+
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    Device, FromSample, OutputCallbackInfo, SampleFormat, SizedSample, Stream, StreamConfig,
+};
+use log::{debug, error, info};
+
+use super::{audio::RingBuffer, AudioSink};
+
+// room for this much sound, the rate control keeps it about half full
+const BUFFER_MILLISECONDS: u32 = 100;
+
+/// The default output device through cpal. Frames queue their samples in a ring buffer that the
+/// device callback drains; on an underrun the last sample is held, which does not click
+pub struct LiveAudio {
+    ring: Arc<Mutex<RingBuffer>>,
+    sample_rate: u32,
+    // playback stops when the stream is dropped
+    _stream: Stream,
+}
+
+impl LiveAudio {
+    pub fn open() -> Result<Self, String> {
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or_else(|| "no audio output device".to_string())?;
+        let supported = device.default_output_config().map_err(|error| error.to_string())?;
+        let config = supported.config();
+        let sample_rate = config.sample_rate.0;
+
+        let capacity = (sample_rate * BUFFER_MILLISECONDS / 1000) as usize;
+        let ring = Arc::new(Mutex::new(RingBuffer::new(capacity)));
+
+        let stream = match supported.sample_format() {
+            SampleFormat::F32 => build_stream::<f32>(&device, &config, ring.clone()),
+            SampleFormat::I16 => build_stream::<i16>(&device, &config, ring.clone()),
+            SampleFormat::U16 => build_stream::<u16>(&device, &config, ring.clone()),
+            format => Err(format!("unsupported device sample format {:?}", format)),
+        }?;
+        stream.play().map_err(|error| error.to_string())?;
+
+        info!(
+            "Audio on {} at {} Hz, {} channels",
+            device.name().unwrap_or_default(), sample_rate, config.channels
+        );
+
+        return Ok(LiveAudio {
+            ring: ring,
+            sample_rate: sample_rate,
+            _stream: stream,
+        });
+    }
+
+    /// The device rate, the emulator has to resample to it
+    pub fn sample_rate(&self) -> u32 {
+        return self.sample_rate;
+    }
+}
+
+fn build_stream<T>(device: &Device, config: &StreamConfig, ring: Arc<Mutex<RingBuffer>>) -> Result<Stream, String>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let channels = config.channels as usize;
+    let mut mono = Vec::new();
+    let mut last_sample = 0.0f32;
+
+    let stream = device.build_output_stream(
+        config,
+        move |data: &mut [T], _: &OutputCallbackInfo| {
+            mono.resize(data.len() / channels, 0.0);
+
+            let available = ring.lock().map_or(0, |mut ring| ring.pop(&mut mono));
+            if available > 0 {
+                last_sample = mono[available - 1];
+            }
+            mono[available..].fill(last_sample);
+
+            // the same mono sample on every channel
+            for (frame, sample) in data.chunks_mut(channels).zip(mono.iter()) {
+                frame.fill(T::from_sample(*sample));
+            }
+        },
+        |error| error!("Audio stream failed, {}", error),
+        None,
+    );
+
+    return stream.map_err(|error| error.to_string());
+}
+
+impl AudioSink for LiveAudio {
+    fn queue_samples(&mut self, samples: &[f32]) {
+        if let Ok(mut ring) = self.ring.lock() {
+            let queued = ring.push(samples);
+            if queued < samples.len() {
+                debug!("Audio buffer full, dropped {} samples", samples.len() - queued);
+            }
+        }
+    }
+
+    fn buffer_fill(&self) -> Option<f32> {
+        return self.ring.lock().ok().map(|ring| ring.fill());
+    }
+}
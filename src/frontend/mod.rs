@@ -0,0 +1,98 @@
+use ::image::{Rgba, RgbaImage};
+
+use self::audio::rate_adjustment;
+use crate::{
+    bus::joypad::Joypad,
+    cpu::fault::CpuFault,
+    nes::{FrameBuffer, Nes},
+    ppu::{PALETTE, SCREEN_HEIGHT, SCREEN_WIDTH},
+};
+
+pub mod audio;
+pub mod config;
+pub mod headless;
+pub mod wav;
+#[cfg(feature = "audio")]
+pub mod live_audio;
+#[cfg(feature = "window")]
+pub mod window;
+
+
+/// Receives every completed frame as palette indices
+pub trait VideoSink {
+    fn present(&mut self, frame: &FrameBuffer);
+}
+
+/// Receives the samples produced during a frame, mono, -1.0..=1.0
+pub trait AudioSink {
+    fn queue_samples(&mut self, samples: &[f32]);
+
+    /// How full the buffer in front of a real-time device is, 0.0 to 1.0. Sinks that are not
+    /// played back live leave it at None and get the nominal sample rate
+    fn buffer_fill(&self) -> Option<f32> {
+        return None;
+    }
+}
+
+/// Sets the joypads up before a frame is emulated
+pub trait InputSource {
+    fn poll(&mut self, frame: u32, joypads: &mut [Joypad; 2]);
+}
+
+/// Drops everything, for frontends that have no screen or no speakers
+pub struct NullSink;
+
+impl VideoSink for NullSink {
+    fn present(&mut self, _frame: &FrameBuffer) {}
+}
+
+impl AudioSink for NullSink {
+    fn queue_samples(&mut self, _samples: &[f32]) {}
+}
+
+/// Nobody touches the joypads
+pub struct NoInput;
+
+impl InputSource for NoInput {
+    fn poll(&mut self, _frame: u32, _joypads: &mut [Joypad; 2]) {}
+}
+
+/// The main loop body shared by all frontends: input, one frame of emulation, then video and audio.
+/// Returns cpu cycles spent on the frame, on a cpu fault nothing is presented
+pub fn step_frame(
+    nes: &mut Nes,
+    frame: u32,
+    input: &mut dyn InputSource,
+    video: &mut dyn VideoSink,
+    audio: &mut dyn AudioSink,
+) -> Result<u64, CpuFault> {
+    input.poll(frame, nes.joypads_mut());
+
+    let cycles = nes.run_frame()?;
+
+    video.present(nes.frame_buffer());
+    audio.queue_samples(nes.audio_samples());
+
+    if let Some(fill) = audio.buffer_fill() {
+        nes.set_audio_rate_adjustment(rate_adjustment(fill));
+    }
+
+    return Ok(cycles);
+}
+
+/// Palette indices to rgba, `image` has to be SCREEN_WIDTH x SCREEN_HEIGHT
+pub fn render_frame(frame: &FrameBuffer, image: &mut RgbaImage) {
+    for y in 0..SCREEN_HEIGHT {
+        for x in 0..SCREEN_WIDTH {
+            let pixel = PALETTE[(frame[y][x] & 0x3F) as usize];
+            image.put_pixel(x as u32, y as u32, Rgba([pixel[0], pixel[1], pixel[2], 255]));
+        }
+    }
+}
+
+pub fn frame_to_image(frame: &FrameBuffer) -> RgbaImage {
+    let mut image = RgbaImage::new(SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32);
+    render_frame(frame, &mut image);
+
+    return image;
+}
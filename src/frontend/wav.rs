@@ -0,0 +1,179 @@
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufWriter, Seek, SeekFrom, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use crate::apu::resampler::sample_to_i16;
+use super::{headless::{FNV_OFFSET_BASIS, FNV_PRIME}, AudioSink};
+
+const HEADER_SIZE: u32 = 44;
+const RIFF_SIZE_OFFSET: u64 = 4;
+const DATA_SIZE_OFFSET: u64 = 40;
+const CHANNELS: u16 = 1;
+
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SampleFormat {
+    #[default]
+    I16,
+    F32,
+}
+
+impl SampleFormat {
+    pub fn bytes_per_sample(&self) -> u16 {
+        return match self {
+            SampleFormat::I16 => 2,
+            SampleFormat::F32 => 4,
+        };
+    }
+
+    fn wave_format(&self) -> u16 {
+        return match self {
+            SampleFormat::I16 => WAVE_FORMAT_PCM,
+            SampleFormat::F32 => WAVE_FORMAT_IEEE_FLOAT,
+        };
+    }
+
+    /// Little endian bytes of `sample` in this format
+    pub fn encode(&self, sample: f32, bytes: &mut Vec<u8>) {
+        match self {
+            SampleFormat::I16 => bytes.extend_from_slice(&sample_to_i16(sample).to_le_bytes()),
+            SampleFormat::F32 => bytes.extend_from_slice(&sample.to_le_bytes()),
+        }
+    }
+}
+
+impl FromStr for SampleFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        return match value.to_ascii_lowercase().as_str() {
+            "i16" | "s16" => Ok(SampleFormat::I16),
+            "f32" => Ok(SampleFormat::F32),
+            _ => Err(format!("unknown sample format {:?}, expected i16 or f32", value)),
+        };
+    }
+}
+
+impl fmt::Display for SampleFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SampleFormat::I16 => "i16",
+            SampleFormat::F32 => "f32",
+        };
+
+        return write!(f, "{}", name);
+    }
+}
+
+/// Mono WAV file of everything the emulator plays. The sizes in the header are filled in by
+/// `finish`, the hash covers the sample data only, so CI can compare runs
+pub struct WavWriter<W: Write + Seek> {
+    writer: W,
+    pub format: SampleFormat,
+    pub sample_rate: u32,
+    data_size: u32,
+    hash: u64,
+    bytes: Vec<u8>,
+    // AudioSink can not fail, the first error is kept for `finish`
+    error: Option<io::Error>,
+}
+
+impl WavWriter<BufWriter<File>> {
+    pub fn create<P: AsRef<Path>>(path: P, sample_rate: u32, format: SampleFormat) -> io::Result<Self> {
+        return Self::new(BufWriter::new(File::create(path)?), sample_rate, format);
+    }
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    pub fn new(mut writer: W, sample_rate: u32, format: SampleFormat) -> io::Result<Self> {
+        write_header(&mut writer, sample_rate, format)?;
+
+        return Ok(WavWriter {
+            writer: writer,
+            format: format,
+            sample_rate: sample_rate,
+            data_size: 0,
+            hash: FNV_OFFSET_BASIS,
+            bytes: Vec::new(),
+            error: None,
+        });
+    }
+
+    /// FNV-1a over the sample bytes written so far
+    pub fn hash(&self) -> u64 {
+        return self.hash;
+    }
+
+    pub fn samples_written(&self) -> u32 {
+        return self.data_size / self.format.bytes_per_sample() as u32;
+    }
+
+    /// Patches the chunk sizes and hands the writer back
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+
+        self.writer.seek(SeekFrom::Start(RIFF_SIZE_OFFSET))?;
+        self.writer.write_all(&(HEADER_SIZE - 8 + self.data_size).to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(DATA_SIZE_OFFSET))?;
+        self.writer.write_all(&self.data_size.to_le_bytes())?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.flush()?;
+
+        return Ok(self.writer);
+    }
+}
+
+// chunk sizes stay zero until `finish`
+fn write_header<W: Write>(writer: &mut W, sample_rate: u32, format: SampleFormat) -> io::Result<()> {
+    let bytes_per_sample = format.bytes_per_sample();
+    let block_align = CHANNELS * bytes_per_sample;
+
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&0u32.to_le_bytes())?;
+    writer.write_all(b"WAVE")?;
+
+    writer.write_all(b"fmt ")?;
+    writer.write_all(&16u32.to_le_bytes())?;
+    writer.write_all(&format.wave_format().to_le_bytes())?;
+    writer.write_all(&CHANNELS.to_le_bytes())?;
+    writer.write_all(&sample_rate.to_le_bytes())?;
+    writer.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+    writer.write_all(&block_align.to_le_bytes())?;
+    writer.write_all(&(bytes_per_sample * 8).to_le_bytes())?;
+
+    writer.write_all(b"data")?;
+    writer.write_all(&0u32.to_le_bytes())?;
+
+    return Ok(());
+}
+
+impl<W: Write + Seek> AudioSink for WavWriter<W> {
+    fn queue_samples(&mut self, samples: &[f32]) {
+        if self.error.is_some() {
+            return;
+        }
+
+        self.bytes.clear();
+        for sample in samples.iter() {
+            self.format.encode(*sample, &mut self.bytes);
+        }
+
+        for byte in self.bytes.iter() {
+            self.hash ^= *byte as u64;
+            self.hash = self.hash.wrapping_mul(FNV_PRIME);
+        }
+
+        match self.writer.write_all(&self.bytes) {
+            Ok(()) => self.data_size += self.bytes.len() as u32,
+            Err(error) => self.error = Some(error),
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use std::{time::SystemTime, collections::HashMap};
+use graphics::{image, Transformed};
+use ::image::RgbaImage;
+use log::{error, warn};
+use piston::{WindowSettings, Event, Loop, EventLoop, EventSettings, Input, Button, ButtonArgs, Key, ButtonState};
+use piston_window::{PistonWindow, Texture, TextureSettings};
+
+use crate::{cartridge::{Cartridge, RomError}, ppu::{SCREEN_WIDTH, SCREEN_HEIGHT, PALETTE}};
+use crate::{bus::joypad::{Joypad, JoypadButtons}, nes::{FrameBuffer, Nes}};
+use super::{
+    config::{KeyBinding, Options},
+    render_frame, step_frame, AudioSink, InputSource, NullSink, VideoSink,
+};
+#[cfg(feature = "audio")]
+use super::live_audio::LiveAudio;
+
+
+const PATTERN_TABLE_SIZE: usize = 128;
+
+macro_rules! key_names {
+    ($($key:ident),* $(,)?) => {
+        [$((stringify!($key), Key::$key)),*]
+    };
+}
+
+// names used for key bindings in the settings file
+const KEY_NAMES: [(&str, Key); 80] = key_names!(
+    A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    D0, D1, D2, D3, D4, D5, D6, D7, D8, D9,
+    NumPad0, NumPad1, NumPad2, NumPad3, NumPad4, NumPad5, NumPad6, NumPad7, NumPad8, NumPad9,
+    Up, Down, Left, Right,
+    Space, Return, Tab, Backspace, LShift, RShift, LCtrl, RCtrl, LAlt, RAlt,
+    Comma, Period, Slash, Semicolon, Minus, Equals, LeftBracket, RightBracket,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+);
+
+fn key_from_name(name: &str) -> Option<Key> {
+    return KEY_NAMES.iter()
+        .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+        .map(|(_, key)| *key);
+}
+
+fn build_input_maps(bindings: &[KeyBinding]) -> [HashMap<Key, JoypadButtons>; 2] {
+    let mut input_maps = [HashMap::new(), HashMap::new()];
+
+    for binding in bindings.iter() {
+        match key_from_name(&binding.key) {
+            Some(key) => {
+                input_maps[binding.player].insert(key, binding.button);
+            },
+            None => {
+                warn!("Unknown key {:?} in bindings for player {}", binding.key, binding.player + 1);
+            },
+        }
+    }
+
+    return input_maps;
+}
+
+fn console_key(name: &str) -> Option<Key> {
+    let key = key_from_name(name);
+    if key.is_none() {
+        warn!("Unknown console button key {:?}", name);
+    }
+
+    return key;
+}
+
+/// Keyboard state collected from piston events, handed to the joypads once per frame
+pub struct KeyboardInput {
+    input_maps: [HashMap<Key, JoypadButtons>; 2],
+    pressed: [JoypadButtons; 2],
+}
+
+impl KeyboardInput {
+    pub fn new(bindings: &[KeyBinding]) -> Self {
+        return KeyboardInput {
+            input_maps: build_input_maps(bindings),
+            pressed: [JoypadButtons::empty(); 2],
+        };
+    }
+
+    pub fn handle_user_input(&mut self, input: &Input) {
+        match &input {
+            Input::Button(button_args) => {
+                if let Button::Keyboard(key) = button_args.button {
+                    for (player, input_map) in self.input_maps.iter().enumerate() {
+                        if let Some(joypad_button) = input_map.get(&key) {
+                            match button_args.state {
+                                ButtonState::Press => {
+                                    self.pressed[player].insert(*joypad_button);
+                                },
+                                ButtonState::Release => {
+                                    self.pressed[player].remove(*joypad_button);
+                                },
+                            }
+                        }
+                    }
+                }
+            },
+            _ => {},
+        } 
+    }
+}
+
+impl InputSource for KeyboardInput {
+    fn poll(&mut self, _frame: u32, joypads: &mut [Joypad; 2]) {
+        for (joypad, pressed) in joypads.iter_mut().zip(self.pressed.iter()) {
+            joypad.buttons_pressed = *pressed;
+        }
+    }
+}
+
+/// The sound card when there is one, silence when muted or built without the `audio` feature.
+/// The console resamples to the rate of the device
+#[cfg_attr(not(feature = "audio"), allow(unused_variables))]
+fn open_audio(mute: bool, nes: &mut Nes) -> Box<dyn AudioSink> {
+    if mute {
+        return Box::new(NullSink);
+    }
+
+    #[cfg(feature = "audio")]
+    match LiveAudio::open() {
+        Ok(audio) => {
+            nes.set_sample_rate(audio.sample_rate());
+
+            return Box::new(audio);
+        },
+        Err(error) => warn!("No sound, {}", error),
+    }
+
+    #[cfg(not(feature = "audio"))]
+    warn!("No sound, built without the audio feature");
+
+    return Box::new(NullSink);
+}
+
+/// Keeps the last presented frame as rgba until the window gets to draw it
+pub struct WindowVideo {
+    pub image_buffer: RgbaImage,
+}
+
+impl WindowVideo {
+    pub fn new() -> Self {
+        return WindowVideo {
+            image_buffer: RgbaImage::new(SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32),
+        };
+    }
+}
+
+impl VideoSink for WindowVideo {
+    fn present(&mut self, frame: &FrameBuffer) {
+        render_frame(frame, &mut self.image_buffer);
+    }
+}
+
+
+pub fn dendy_run(options: Options) -> Result<(), RomError> {
+    warn!("Started app");
+
+    let settings = &options.settings;
+
+    let cartridge = Cartridge::from_path(&options.rom_path)?;
+    let mut nes = Nes::with_power_on_ram(cartridge, settings.region, settings.power_on_ram);
+    let reset_key = console_key(&settings.reset_key);
+    let power_cycle_key = console_key(&settings.power_cycle_key);
+
+    let mut input = KeyboardInput::new(&settings.bindings);
+    let mut video = WindowVideo::new();
+    let mut audio = open_audio(settings.mute, &mut nes);
+    let mut frame = 0;
+    // emulation stops on the first cpu fault, the last frame stays on screen
+    let mut halted = false;
+
+    let scale = settings.scale as usize;
+    let window_width = SCREEN_WIDTH * scale + PATTERN_TABLE_SIZE * 2;
+    let window_height = (SCREEN_HEIGHT * scale).max(PATTERN_TABLE_SIZE);
+
+    let mut window: PistonWindow = WindowSettings::new(
+        "Dendynes emulator", [window_width as f64, window_height as f64]
+    ).exit_on_esc(true)
+     .build()
+     .unwrap();
+    
+    let mut event_settings = EventSettings::new();
+    event_settings.ups = settings.region.timing().frames_per_second.round() as u64;
+    window.set_event_settings(event_settings);
+
+    let mut table_image_buffer_1 = RgbaImage::new(PATTERN_TABLE_SIZE as u32, PATTERN_TABLE_SIZE as u32);
+    let mut table_image_buffer_2 = RgbaImage::new(PATTERN_TABLE_SIZE as u32, PATTERN_TABLE_SIZE as u32);
+
+    let mut texture_context = &mut window.create_texture_context();
+
+    let mut texture = Texture::from_image(
+        texture_context, &video.image_buffer, &TextureSettings::new()
+    ).unwrap();
+
+    let mut table_texture_1 = Texture::from_image(
+        texture_context, &table_image_buffer_1, &TextureSettings::new()
+    ).unwrap();
+    let mut table_texture_2 = Texture::from_image(
+        texture_context, &table_image_buffer_2, &TextureSettings::new()
+    ).unwrap();
+
+
+    while let Some(event) = window.next() {
+        match event {
+            Event::Input(input_event, _) => {
+                // both bring a faulted cpu back
+                if let Input::Button(ButtonArgs { button: Button::Keyboard(key), state: ButtonState::Press, .. }) = input_event {
+                    if Some(key) == reset_key {
+                        nes.soft_reset();
+                        halted = false;
+                    } else if Some(key) == power_cycle_key {
+                        nes.power_cycle();
+                        halted = false;
+                    }
+                }
+
+                input.handle_user_input(&input_event);
+            },
+            Event::Loop(kind) => {
+                match kind {
+                    Loop::Update(args) => {
+                        if halted {
+                            continue;
+                        }
+
+                        let start = SystemTime::now();
+                        if let Err(fault) = step_frame(&mut nes, frame, &mut input, &mut video, audio.as_mut()) {
+                            error!("Emulation stopped, {}", fault);
+                            halted = true;
+                        }
+                        frame = frame.wrapping_add(1);
+                        let end = SystemTime::now();
+
+                        // println!(
+                        //     "Update took: dt {}; clock: {}ms; {}s",
+                        //     args.dt,
+                        //     end.duration_since(start).unwrap().as_millis(),
+                        //     end.duration_since(start).unwrap().as_secs_f32(),
+                        // );
+                    },
+                    Loop::Render(_args) => {
+                        window.draw_2d(&event, |c, g, d| {
+                            let start = SystemTime::now();
+                            let end1 = SystemTime::now();
+
+                            {
+                                nes.ppu_mut().draw_pattern_tables();
+                            }
+                            for y in 0..PATTERN_TABLE_SIZE {
+                                for x in 0..PATTERN_TABLE_SIZE {
+                                    let nes_color = nes.ppu().debug_pattern_tables[0][y][x];
+                                    let pixel = PALETTE[nes_color as usize];
+                                    table_image_buffer_1.put_pixel(x as u32, y as u32, ::image::Rgba(
+                                        [pixel[0], pixel[1], pixel[2], 255],
+                                    )
+                                    );
+                                }
+                            } 
+                            for y in 0..PATTERN_TABLE_SIZE {
+                                for x in 0..PATTERN_TABLE_SIZE {
+                                    let nes_color = nes.ppu().debug_pattern_tables[1][y][x];
+                                    let pixel = PALETTE[nes_color as usize];
+                                    table_image_buffer_2.put_pixel(x as u32, y as u32, ::image::Rgba(
+                                        [pixel[0], pixel[1], pixel[2], 255],
+                                    )
+                                    );
+                                }
+                            }               
+
+                            // println!("######### RENDER");
+                            // println!("{:?}", nes.frame_buffer());
+
+                            texture.update(texture_context, &video.image_buffer).unwrap();
+                            table_texture_1.update(texture_context, &table_image_buffer_1).unwrap();
+                            table_texture_2.update(texture_context, &table_image_buffer_1).unwrap();
+                            
+                            image(&texture, c.transform.scale(scale as f64, scale as f64), g);
+                            image(&table_texture_1, c.transform.trans((SCREEN_WIDTH * scale) as f64, 0f64), g);
+                            image(&table_texture_2, c.transform.trans((SCREEN_WIDTH * scale + PATTERN_TABLE_SIZE) as f64, 0f64), g);
+                            let end2 = SystemTime::now();
+
+                            texture_context.encoder.flush(d);
+                            let end3 = SystemTime::now();
+                            // println!(
+                            //     "Draw took: {}; Texture update: {}; Flush: {}",
+                            //     end1.duration_since(start).unwrap().as_secs_f32(),
+                            //     end2.duration_since(start).unwrap().as_secs_f32(),
+                            //     end3.duration_since(start).unwrap().as_secs_f32()
+                            // )
+                        });
+                    },
+                    _ => {},
+                }
+            },
+            _ => {}
+        }
+    }
+
+    return Ok(());
+}
\ No newline at end of file
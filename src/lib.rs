@@ -0,0 +1,12 @@
+pub mod apu;
+pub mod bus;
+pub mod cpu;
+pub mod memory;
+pub mod cartridge;
+pub mod clock;
+pub mod frontend;
+pub mod logging;
+pub mod nes;
+pub mod power_on;
+pub mod ppu;
+pub mod region;
@@ -1,8 +1,23 @@
 use std::{time::SystemTime, fs::OpenOptions};
-use std::io::stdout;
+use std::io::{stdout, stderr};
 
-pub fn init_logger() -> Result<(), fern::InitError> {
+use log::LevelFilter;
+
+pub fn init_logger(level: LevelFilter) -> Result<(), fern::InitError> {
     fern::Dispatch::new()
+        .chain(
+            fern::Dispatch::new()
+            .format(|out, message, record| {
+                out.finish(format_args!(
+                    "[{} {}] {}",
+                    record.level(),
+                    record.target(),
+                    message
+                ))
+            })
+            .level(level)
+            .chain(stderr())
+        )
         // .chain(
         //     fern::Dispatch::new()
         //     .format(|out, message, record| {
@@ -1,7 +1,23 @@
-use dendynes::dendy_run;
+use std::{env, process::exit};
 
-pub mod dendynes;
+use dendynes::{
+    frontend::{config::{Options, USAGE}, window::dendy_run},
+    logging::init_logger,
+};
 
 fn main() {
-    dendy_run();
-}
\ No newline at end of file
+    let options = match Options::load(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            exit(2);
+        },
+    };
+
+    init_logger(options.settings.log_level).unwrap();
+
+    if let Err(error) = dendy_run(options) {
+        eprintln!("{}", error);
+        exit(1);
+    }
+}
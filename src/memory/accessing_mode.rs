@@ -1,4 +1,4 @@
-use crate::dendynes::cpu::processor::CPU;
+use crate::cpu::{bus::CpuBus, processor::CPU};
 
 
 const ZERO_PAGE: u16 = 0;
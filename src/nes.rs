@@ -0,0 +1,153 @@
+use std::{cell::RefCell, rc::Rc};
+
+use log::info;
+
+use super::{
+    bus::{Bus, joypad::Joypad},
+    cartridge::Cartridge,
+    cpu::{fault::CpuFault, processor::CPU},
+    power_on::PowerOnRam,
+    ppu::{PPU, SCREEN_HEIGHT, SCREEN_WIDTH},
+    region::Region,
+};
+
+// random power-on contents of each memory come from their own stream
+const CPU_RAM_STREAM: u64 = 0;
+const NAMETABLES_STREAM: u64 = 1;
+const OAM_STREAM: u64 = 2;
+const PALETTE_STREAM: u64 = 3;
+
+pub type FrameBuffer = [[u8; SCREEN_WIDTH]; SCREEN_HEIGHT];
+
+/// The whole console: owns the CPU, which owns the bus, which owns the PPU.
+/// The cartridge is shared between the bus and the PPU, same as on the real board.
+pub struct Nes {
+    pub cpu: CPU,
+    cartridge: Rc<RefCell<Cartridge>>,
+    region: Region,
+    power_on_ram: PowerOnRam,
+}
+
+impl Nes {
+    /// NTSC console
+    pub fn new(cartridge: Cartridge) -> Self {
+        return Self::with_region(cartridge, Region::Ntsc);
+    }
+
+    pub fn with_region(cartridge: Cartridge, region: Region) -> Self {
+        return Self::with_power_on_ram(cartridge, region, PowerOnRam::default());
+    }
+
+    pub fn with_power_on_ram(cartridge: Cartridge, region: Region, power_on_ram: PowerOnRam) -> Self {
+        let cartridge = Rc::new(RefCell::new(cartridge));
+
+        return Nes {
+            cpu: Self::wire_up(cartridge.clone(), region, power_on_ram),
+            cartridge: cartridge,
+            region: region,
+            power_on_ram: power_on_ram,
+        };
+    }
+
+    fn wire_up(cartridge: Rc<RefCell<Cartridge>>, region: Region, power_on_ram: PowerOnRam) -> CPU {
+        let mut ppu = PPU::new(cartridge.clone(), region);
+        power_on_ram.fill(&mut ppu.memory, NAMETABLES_STREAM);
+        power_on_ram.fill(&mut ppu.oam_data, OAM_STREAM);
+        power_on_ram.fill(&mut ppu.palette, PALETTE_STREAM);
+        // palette entries are 6 bits wide
+        for color in ppu.palette.iter_mut() {
+            *color &= 0x3F;
+        }
+
+        let mut bus = Bus::new(ppu, cartridge, region);
+        power_on_ram.fill(&mut bus.cpu_memory, CPU_RAM_STREAM);
+
+        return CPU::new(bus);
+    }
+
+    /// Switches the console off and on: RAM and the PPU memories get the power-on contents,
+    /// every chip starts over, the inserted cartridge stays
+    pub fn power_cycle(&mut self) {
+        info!("Power cycle, ram {}", self.power_on_ram);
+        let sample_rate = self.sample_rate();
+
+        self.cpu = Self::wire_up(self.cartridge.clone(), self.region, self.power_on_ram);
+        self.cpu.bus.apu.set_sample_rate(sample_rate);
+    }
+
+    /// The RESET button: RAM survives, the CPU runs its reset sequence, the PPU clears
+    /// its control registers
+    pub fn soft_reset(&mut self) {
+        info!("Soft reset");
+        self.cpu.bus.reset();
+        self.cpu.reset();
+    }
+
+    pub fn region(&self) -> Region {
+        return self.region;
+    }
+
+    pub fn power_on_ram(&self) -> PowerOnRam {
+        return self.power_on_ram;
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        return self.cpu.bus.apu.sample_rate();
+    }
+
+    /// Output rate of `audio_samples`, `apu::DEFAULT_SAMPLE_RATE` until set
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.cpu.bus.apu.set_sample_rate(sample_rate);
+    }
+
+    /// Stretches the audio output rate by `factor`, close to 1.0, see `frontend::audio::rate_adjustment`
+    pub fn set_audio_rate_adjustment(&mut self, factor: f64) {
+        self.cpu.bus.apu.set_rate_adjustment(factor);
+    }
+
+    /// Audio produced since the last call, mono, -1.0..=1.0
+    pub fn audio_samples(&mut self) -> &[f32] {
+        return self.cpu.bus.apu.drain_samples();
+    }
+
+    pub fn step_instruction(&mut self) -> Result<u64, CpuFault> {
+        return self.cpu.cpu_step();
+    }
+
+    /// Runs instructions until the PPU reports a completed frame, stops early on a cpu fault.
+    /// A jammed cpu keeps failing every step until `soft_reset` or `power_cycle`
+    pub fn run_frame(&mut self) -> Result<u64, CpuFault> {
+        let mut cycles = 0;
+        self.cpu.bus.ppu.completed_frame = false;
+
+        while !self.cpu.bus.ppu.completed_frame {
+            cycles += self.step_instruction()?;
+        }
+
+        return Ok(cycles);
+    }
+
+    pub fn frame_buffer(&self) -> &FrameBuffer {
+        return &self.cpu.bus.ppu.screen;
+    }
+
+    pub fn bus(&self) -> &Bus {
+        return &self.cpu.bus;
+    }
+
+    pub fn bus_mut(&mut self) -> &mut Bus {
+        return &mut self.cpu.bus;
+    }
+
+    pub fn ppu(&self) -> &PPU {
+        return &self.cpu.bus.ppu;
+    }
+
+    pub fn ppu_mut(&mut self) -> &mut PPU {
+        return &mut self.cpu.bus.ppu;
+    }
+
+    pub fn joypads_mut(&mut self) -> &mut [Joypad; 2] {
+        return &mut self.cpu.bus.joypads;
+    }
+}
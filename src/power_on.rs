@@ -0,0 +1,78 @@
+use std::{fmt, str::FromStr, time::{SystemTime, UNIX_EPOCH}};
+
+const RANDOM_PREFIX: &str = "random";
+
+/// What the console RAM and the PPU memories hold right after power on. Real chips come up
+/// with a mostly random pattern, some games and test ROMs read it before writing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PowerOnRam {
+    #[default]
+    Zeros,
+    // every byte $FF
+    Ones,
+    // noise from the seed, a new seed on every power cycle without one
+    Random(Option<u64>),
+}
+
+impl PowerOnRam {
+    /// Fills `memory`, `stream` keeps the random contents of different memories apart
+    pub fn fill(&self, memory: &mut [u8], stream: u64) {
+        match self {
+            PowerOnRam::Zeros => memory.fill(0x00),
+            PowerOnRam::Ones => memory.fill(0xFF),
+            PowerOnRam::Random(seed) => {
+                let seed = seed.unwrap_or_else(|| {
+                    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u64)
+                });
+                let mut state = seed ^ stream.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+
+                for chunk in memory.chunks_mut(8) {
+                    let bytes = splitmix64(&mut state).to_le_bytes();
+                    chunk.copy_from_slice(&bytes[..chunk.len()]);
+                }
+            },
+        }
+    }
+}
+
+// https://prng.di.unimi.it/splitmix64.c
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+    let mut result = *state;
+    result = (result ^ (result >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    result = (result ^ (result >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+    return result ^ (result >> 31);
+}
+
+impl FromStr for PowerOnRam {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.to_ascii_lowercase();
+
+        return match value.as_str() {
+            "zeros" | "00" => Ok(PowerOnRam::Zeros),
+            "ones" | "ff" => Ok(PowerOnRam::Ones),
+            RANDOM_PREFIX => Ok(PowerOnRam::Random(None)),
+            _ => match value.strip_prefix(RANDOM_PREFIX).and_then(|seed| seed.strip_prefix(':')) {
+                Some(seed) => seed.parse::<u64>()
+                    .map(|seed| PowerOnRam::Random(Some(seed)))
+                    .map_err(|_| format!("bad random seed {:?}", seed)),
+                None => Err(format!("unknown power-on ram {:?}, expected zeros, ff, random or random:<seed>", value)),
+            },
+        };
+    }
+}
+
+impl fmt::Display for PowerOnRam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            PowerOnRam::Zeros => write!(f, "zeros"),
+            PowerOnRam::Ones => write!(f, "ff"),
+            PowerOnRam::Random(None) => write!(f, "{}", RANDOM_PREFIX),
+            PowerOnRam::Random(Some(seed)) => write!(f, "{}:{}", RANDOM_PREFIX, seed),
+        };
+    }
+}
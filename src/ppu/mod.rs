@@ -4,14 +4,14 @@ pub mod oam;
 use core::panic;
 use std::{cell::RefCell, rc::Rc};
 
-use log::{warn, error, debug};
+use log::{error, debug};
 
 use self::{registers::{Controller, Mask, Status, LoopyRegister}, oam::OamSprite};
 
-use super::cartridge::{Cartridge, Mirroring};
+use super::{cartridge::{Cartridge, Mirroring}, region::{Region, RegionTiming}};
 
-pub const SCANLINES_COUNT: i32 = 262;
-pub const SCANLINES_PER_FRAME: i32 = 241;
+// first line after the visible ones, same in every region
+pub const POST_RENDER_SCANLINE: i32 = 240;
 pub const CYCLES_TO_DRAW_SCANLINE: usize = 341;
 pub const VISIBLE_SCANLINE_CYCLES: usize = 256;
 pub const PPU_MEMORY_SIZE: usize = 0x800;
@@ -109,7 +109,8 @@ pub const PALETTE: [[u8; 3]; 64] = [
 pub struct PPU {
     pub total_cycles: usize,
     pub cycles: usize,
-    pub nmi_interrupt: bool,
+    // $2002 was read on the dot before vblank, the flag stays clear for this frame
+    pub vblank_suppressed: bool,
 
     pub latch: bool,
     pub control_register: Controller,
@@ -152,6 +153,7 @@ pub struct PPU {
     pub cartridge: Rc<RefCell<Cartridge>>,
 
     pub scanlines: i32,
+    pub timing: RegionTiming,
     // pub mirroring: Mirroring,
 
     pub completed_frame: bool,
@@ -167,11 +169,11 @@ fn flip_byte_util(mut byte: u8) -> u8 {
 }
 
 impl PPU {
-    pub fn new(cartridge: Rc<RefCell<Cartridge>>) -> Self {
+    pub fn new(cartridge: Rc<RefCell<Cartridge>>, region: Region) -> Self {
         let mut ppu = PPU {
             total_cycles: 0,
             cycles: 0,
-            nmi_interrupt: false,
+            vblank_suppressed: false,
             latch: false,
             control_register: Controller::empty(),
             // mask_register: Mask::from_bits_truncate(0b00011110),
@@ -208,6 +210,7 @@ impl PPU {
             debug_pattern_tables: [[[0; 128]; 128];2],
             cartridge: cartridge,
             scanlines: 0,
+            timing: region.timing(),
             completed_frame: false,
             odd_frame: false,
         };
@@ -215,6 +218,19 @@ impl PPU {
         return ppu;
     }
 
+    /// The console RESET button: PPUCTRL, PPUMASK, the scroll and the write latch are cleared,
+    /// OAMADDR, PPUADDR, vblank and all memories stay as they are
+    pub fn reset(&mut self) {
+        self.control_register = Controller::empty();
+        self.mask_register = Mask::empty();
+        self.latch = false;
+        self.temp_address_register = LoopyRegister::new();
+        self.fine_x = 0;
+        self.data_buffer = 0;
+        self.odd_frame = false;
+        self.vblank_suppressed = false;
+    }
+
     pub fn draw_pattern_tables(&mut self) {
         for i in 0..self.debug_pattern_tables.len() {
             for y in 0..16 {
@@ -244,6 +260,10 @@ impl PPU {
 
     /* +register reads */
     pub fn read_status_register(&mut self) -> u8 {
+        if self.scanlines == self.timing.vblank_scanline && self.cycles == 1 {
+            self.vblank_suppressed = true;
+        }
+
         let result = self.status_register.read(self.data_buffer);
         
         self.status_register.remove(Status::VERTICAL_BLANK);
@@ -272,6 +292,22 @@ impl PPU {
         return self.get_oam_data_value();
     }
 
+    /// What a $2002 read would return, without clearing vblank or the write latch
+    pub fn peek_status_register(&self) -> u8 {
+        return self.status_register.peek(self.data_buffer);
+    }
+
+    /// What a $2007 read would return, without refilling the buffer or moving the address
+    pub fn peek_data_register(&self) -> u8 {
+        let address = self.address_register.address();
+
+        if address >= PALETTE_PAGE_START {
+            return self.read_u8(address);
+        }
+
+        return self.data_buffer;
+    }
+
     pub fn read_data_register(&mut self) -> u8 {
         let mut result = self.data_buffer;
         let address = self.address_register.address();
@@ -370,10 +406,10 @@ impl PPU {
         self.address_register.increment(vertical_mode);
     }
 
-    fn read_from_internal_memory(&mut self, address: usize) -> u8 {
+    fn read_from_internal_memory(&self, address: usize) -> u8 {
         let mirrored_address = address & (NAMETABLE_MIRROR_MASK as usize);
         let name_table_index = mirrored_address / PPU_MEMORY_SIZE;
-        let mirroring = self.cartridge.borrow_mut().mirroring;
+        let mirroring = self.cartridge.borrow().mirroring;
 
         match mirroring {
             Mirroring::Horizontal => {
@@ -475,7 +511,7 @@ impl PPU {
         }
     }
 
-    pub fn read_u8(&mut self, address: u16) -> u8 {
+    pub fn read_u8(&self, address: u16) -> u8 {
         // let address = self.address_register.get();
         let result;
         let address = address & 0x3FFF;
@@ -535,7 +571,7 @@ impl PPU {
     }
 
     fn is_end_of_frame(&self) -> bool {
-        return self.scanlines == SCANLINES_PER_FRAME && self.cycles == 1;
+        return self.scanlines == self.timing.vblank_scanline && self.cycles == 1;
     }
 
     fn clear_sprites(&mut self) {
@@ -602,19 +638,25 @@ impl PPU {
         }
     }
 
-    fn check_nmi_interrupt(&mut self) {
-        if self.scanlines >= SCANLINES_PER_FRAME && self.scanlines < SCANLINES_COUNT {
+    fn check_vertical_blank(&mut self) {
+        if self.scanlines >= self.timing.vblank_scanline && self.scanlines < self.timing.scanlines_per_frame {
             if  self.is_end_of_frame() {
-                self.status_register.insert(Status::VERTICAL_BLANK);
-
-                if self.control_register.contains(Controller::GENERATE_NMI) {
-                    warn!("Generating NMI");
-                    self.nmi_interrupt = true;
+                if !self.vblank_suppressed {
+                    self.status_register.insert(Status::VERTICAL_BLANK);
                 }
+                self.vblank_suppressed = false;
             }
         }
     }
 
+    /// Level of the /NMI output: vblank flag and PPUCTRL bit 7 together. The cpu reacts to
+    /// its rising edge, so enabling NMI during vblank fires one and a $2002 read that clears
+    /// vblank right as it is set swallows it
+    pub fn nmi_line(&self) -> bool {
+        return self.status_register.contains(Status::VERTICAL_BLANK)
+            && self.control_register.contains(Controller::GENERATE_NMI);
+    }
+
     fn get_background_pixel_info(&self) -> (u8, u8) {
         if self.mask_register.is_background_enabled() {
             if self.mask_register.contains(Mask::LEFTMOST_SHOW_BACKGROUND) && self.cycles > 8 {
@@ -871,8 +913,9 @@ impl PPU {
     }
 
     pub fn tick(&mut self) {
-        if self.scanlines >= -1 && self.scanlines < (SCANLINES_PER_FRAME - 1) {
-            if self.scanlines == 0 && self.cycles == 0 && self.mask_register.is_render_enabled() {
+        if self.scanlines >= -1 && self.scanlines < POST_RENDER_SCANLINE {
+            if self.scanlines == 0 && self.cycles == 0 && self.mask_register.is_render_enabled()
+                && self.timing.skips_odd_frame_dot && self.odd_frame {
                 self.cycles = 1;
             }
 
@@ -913,7 +956,7 @@ impl PPU {
             } 
         }
 
-        self.check_nmi_interrupt();
+        self.check_vertical_blank();
 
         let (background_pixel, background_pixel_palette) = self.get_background_pixel_info();
         let (foreground_pixel, foreground_pixel_palette, foreground_priority) = self.get_sprite_pixel_info();
@@ -948,7 +991,8 @@ impl PPU {
             self.cycles = 0;
             self.scanlines += 1;
 
-            if self.scanlines >= SCANLINES_COUNT {
+            // the pre-render line counts as -1, so the last one is scanlines_per_frame - 2
+            if self.scanlines >= self.timing.scanlines_per_frame - 1 {
                 self.scanlines = -1;
                 self.completed_frame = true;
                 self.odd_frame = !self.odd_frame;
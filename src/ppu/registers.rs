@@ -211,6 +211,11 @@ impl Status {
         return result;
     }
 
+    /// Same value as `read`, vblank stays set
+    pub fn peek(&self, open_bus: u8) -> u8 {
+        return self.bits() | (open_bus & OPEN_BUS_REGISTER_MASK);
+    }
+
     pub fn reset(&mut self) {
         self.remove(Status::SPRITE_OVERFLOW);
         self.remove(Status::SPRITE_ZERO_HIT);
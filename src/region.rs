@@ -0,0 +1,79 @@
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Region {
+    #[default]
+    Ntsc,
+    Pal,
+    Dendy,
+}
+
+/// Frame layout of the ppu in each region.
+/// Scanline -1 is the pre-render line, 0..=239 are visible, then post-render lines up to vblank
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegionTiming {
+    // pre-render line included
+    pub scanlines_per_frame: i32,
+    // vblank flag and NMI go up on dot 1 of this line
+    pub vblank_scanline: i32,
+    // NTSC drops the idle dot of the pre-render line on odd frames when rendering is on
+    pub skips_odd_frame_dot: bool,
+    pub frames_per_second: f64,
+}
+
+pub const NTSC_TIMING: RegionTiming = RegionTiming {
+    scanlines_per_frame: 262,
+    vblank_scanline: 241,
+    skips_odd_frame_dot: true,
+    frames_per_second: 60.0988,
+};
+
+pub const PAL_TIMING: RegionTiming = RegionTiming {
+    scanlines_per_frame: 312,
+    vblank_scanline: 241,
+    skips_odd_frame_dot: false,
+    frames_per_second: 50.0070,
+};
+
+// 50 Hz like PAL, but with NTSC-like 20 lines of vblank: post-render lies until line 291
+pub const DENDY_TIMING: RegionTiming = RegionTiming {
+    scanlines_per_frame: 312,
+    vblank_scanline: 291,
+    skips_odd_frame_dot: false,
+    frames_per_second: 50.0070,
+};
+
+impl Region {
+    pub fn timing(&self) -> RegionTiming {
+        return match self {
+            Region::Ntsc => NTSC_TIMING,
+            Region::Pal => PAL_TIMING,
+            Region::Dendy => DENDY_TIMING,
+        };
+    }
+}
+
+impl FromStr for Region {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        return match value.to_ascii_lowercase().as_str() {
+            "ntsc" => Ok(Region::Ntsc),
+            "pal" => Ok(Region::Pal),
+            "dendy" => Ok(Region::Dendy),
+            _ => Err(format!("unknown region {:?}, expected ntsc, pal or dendy", value)),
+        };
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Region::Ntsc => "ntsc",
+            Region::Pal => "pal",
+            Region::Dendy => "dendy",
+        };
+
+        return write!(f, "{}", name);
+    }
+}
@@ -0,0 +1,327 @@
+use dendynes::{
+    apu::{ApuStatus, APU},
+    bus::Bus,
+    cartridge::Cartridge,
+    cpu::bus::CpuBus,
+    nes::Nes,
+    region::Region,
+};
+
+fn run(apu: &mut APU, cycles: u32) {
+    for _ in 0..cycles {
+        apu.tick();
+    }
+}
+
+// cycles until the frame interrupt goes up after a $4017 write
+fn cycles_to_frame_interrupt(region: Region) -> u32 {
+    let mut apu = APU::new(region);
+    apu.write_register(0x4017, 0x00);
+
+    let mut cycles = 0;
+    while !apu.frame_interrupt() {
+        apu.tick();
+        cycles += 1;
+        assert!(cycles < 100_000, "no frame interrupt in {}", region);
+    }
+
+    return cycles;
+}
+
+#[test]
+fn frame_interrupt_follows_the_region_sequence() {
+    // 3 cycles of write delay, then the last steps of the 4-step sequence
+    assert_eq!(cycles_to_frame_interrupt(Region::Ntsc), 3 + 29828);
+    assert_eq!(cycles_to_frame_interrupt(Region::Dendy), 3 + 29828);
+    assert_eq!(cycles_to_frame_interrupt(Region::Pal), 3 + 33252);
+}
+
+#[test]
+fn status_read_acknowledges_the_frame_interrupt() {
+    let mut apu = APU::new(Region::Ntsc);
+    run(&mut apu, 30_000);
+
+    assert!(apu.read_status() & ApuStatus::FRAME_INTERRUPT.bits() > 0);
+    assert!(!apu.frame_interrupt());
+
+    // inhibit keeps it down for good and clears a pending one
+    apu.write_register(0x4017, 0x40);
+    run(&mut apu, 100_000);
+    assert!(!apu.frame_interrupt());
+
+    // the 5-step sequence never raises it
+    apu.write_register(0x4017, 0x80);
+    run(&mut apu, 100_000);
+    assert!(!apu.frame_interrupt());
+}
+
+#[test]
+fn length_counter_runs_out_on_half_frames() {
+    let mut apu = APU::new(Region::Ntsc);
+    apu.write_register(0x4015, 0b01);
+    apu.write_register(0x4017, 0x40);
+    // constant volume 15, 50% duty, length index 1 = 254 half frames
+    apu.write_register(0x4000, 0b1001_1111);
+    apu.write_register(0x4002, 0xFF);
+    apu.write_register(0x4003, 0b0000_1000);
+    assert_eq!(apu.peek_status() & 0b11, 0b01);
+
+    // two half frames per 4-step sequence
+    run(&mut apu, 126 * 29830 + 3);
+    assert_eq!(apu.pulses[0].length_counter.counter, 2);
+    run(&mut apu, 29830);
+    assert_eq!(apu.peek_status() & 0b11, 0);
+    assert_eq!(apu.pulses[0].output(), 0);
+}
+
+#[test]
+fn disabling_a_channel_clears_its_length_counter() {
+    let mut apu = APU::new(Region::Ntsc);
+
+    // a disabled channel ignores length loads
+    apu.write_register(0x4007, 0b0000_1000);
+    assert_eq!(apu.pulses[1].length_counter.counter, 0);
+
+    apu.write_register(0x4015, 0b10);
+    apu.write_register(0x4007, 0b0000_1000);
+    assert_eq!(apu.peek_status() & 0b11, 0b10);
+
+    apu.write_register(0x4015, 0b00);
+    assert_eq!(apu.peek_status() & 0b11, 0);
+}
+
+#[test]
+fn sweep_negates_with_ones_complement_on_pulse_1() {
+    let mut apu = APU::new(Region::Ntsc);
+    apu.write_register(0x4015, 0b11);
+    for base in [0x4000, 0x4004] {
+        // enabled, divider period 0, negate, shift 1
+        apu.write_register(base + 1, 0b1000_1001);
+        apu.write_register(base + 2, 0x00);
+        apu.write_register(base + 3, 0b0000_1001);
+    }
+
+    // 5-step mode clocks a half frame right after the write delay
+    apu.write_register(0x4017, 0xC0);
+    run(&mut apu, 4);
+
+    assert_eq!(apu.pulses[0].timer_period, 0x100 - 0x80 - 1);
+    assert_eq!(apu.pulses[1].timer_period, 0x100 - 0x80);
+}
+
+#[test]
+fn sweep_target_mutes_even_when_disabled() {
+    let mut apu = APU::new(Region::Ntsc);
+    apu.write_register(0x4015, 0b01);
+    apu.write_register(0x4000, 0b1011_1111);
+    // sweep disabled, shift 0: the target is twice the period, past $7FF
+    apu.write_register(0x4001, 0x00);
+    apu.write_register(0x4002, 0x00);
+    apu.write_register(0x4003, 0b0000_1100);
+
+    for _ in 0..64 {
+        apu.tick();
+        assert_eq!(apu.pulses[0].output(), 0);
+    }
+}
+
+// noise levels sampled once per shift of the LFSR at the shortest period
+fn noise_sequence(short_mode: bool, length: usize) -> Vec<u8> {
+    let mut apu = APU::new(Region::Ntsc);
+    apu.write_register(0x4015, 0b1000);
+    // halted length, constant volume 1
+    apu.write_register(0x400C, 0b0011_0001);
+    apu.write_register(0x400E, if short_mode { 0x80 } else { 0x00 });
+    apu.write_register(0x400F, 0b0000_1000);
+
+    return (0..length).map(|_| {
+        run(&mut apu, 4);
+
+        apu.noise.output()
+    }).collect();
+}
+
+fn repeats_every(sequence: &[u8], period: usize) -> bool {
+    return sequence.iter().zip(sequence[period..].iter()).all(|(a, b)| a == b);
+}
+
+#[test]
+fn noise_lfsr_loops_after_32767_or_93_steps() {
+    let long = noise_sequence(false, 2 * 32767);
+    assert!(repeats_every(&long, 32767));
+    // 32767 = 7 * 31 * 151
+    for period in [32767 / 7, 32767 / 31, 32767 / 151] {
+        assert!(!repeats_every(&long, period), "long mode repeats every {}", period);
+    }
+
+    let short = noise_sequence(true, 4 * 93);
+    assert!(repeats_every(&short, 93));
+    assert!(!repeats_every(&short, 31));
+    assert!(!repeats_every(&short, 3));
+}
+
+#[test]
+fn noise_period_table_follows_the_region() {
+    let mut ntsc = APU::new(Region::Ntsc);
+    let mut dendy = APU::new(Region::Dendy);
+    let mut pal = APU::new(Region::Pal);
+    for apu in [&mut ntsc, &mut dendy, &mut pal] {
+        apu.write_register(0x400E, 0x0F);
+    }
+
+    assert_eq!(ntsc.noise.timer_period, 4068);
+    assert_eq!(dendy.noise.timer_period, 4068);
+    assert_eq!(pal.noise.timer_period, 3778);
+}
+
+fn triangle_levels(apu: &mut APU, cycles: u32) -> Vec<u8> {
+    return (0..cycles).map(|_| {
+        apu.tick();
+
+        apu.triangle.output()
+    }).collect();
+}
+
+#[test]
+fn triangle_steps_only_with_both_counters_running() {
+    let mut apu = APU::new(Region::Ntsc);
+    apu.write_register(0x4015, 0b0100);
+    apu.write_register(0x4017, 0x40);
+    // linear counter 0x10, period 0x20
+    apu.write_register(0x4008, 0x10);
+    apu.write_register(0x400A, 0x20);
+    apu.write_register(0x400B, 0b0000_1000);
+
+    // the linear counter is reloaded on the first quarter frame only
+    let levels = triangle_levels(&mut apu, 7460);
+    assert!(levels.iter().all(|level| *level == 15));
+
+    // one step per period + 1 cpu cycles, a full 32 step wave
+    let levels = triangle_levels(&mut apu, 33 * 32);
+    assert_eq!(levels[33 * 15], 0);
+    assert_eq!(*levels.iter().max().unwrap(), 15);
+
+    // 0x10 quarter frames later the linear counter runs out and the level holds
+    run(&mut apu, 0x10 * 7457);
+    let level = apu.triangle.output();
+    assert!(triangle_levels(&mut apu, 33 * 32).iter().all(|sample| *sample == level));
+}
+
+#[test]
+fn triangle_holds_at_ultrasonic_periods() {
+    let mut apu = APU::new(Region::Ntsc);
+    apu.write_register(0x4015, 0b0100);
+    // control set: the linear counter never runs out
+    apu.write_register(0x4008, 0xFF);
+    apu.write_register(0x400A, 0x01);
+    apu.write_register(0x400B, 0b0000_1000);
+
+    let levels = triangle_levels(&mut apu, 100_000);
+    assert!(levels.iter().all(|level| *level == 15));
+}
+
+// NROM-256 with the low byte of each PRG offset as its contents
+fn nrom_console() -> Nes {
+    let mut rom = vec![b'N', b'E', b'S', 0x1A, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    rom.extend((0..0x8000).map(|offset| offset as u8));
+    rom.extend(vec![0; 0x2000]);
+
+    return Nes::new(Cartridge::from_bytes(&rom).unwrap());
+}
+
+// sample of `length` bytes from $C040, started through $4015
+fn start_sample(bus: &mut Bus, control: u8, length: u8) {
+    bus.write_memory_u8(0x4010, control);
+    bus.write_memory_u8(0x4012, 0x01);
+    bus.write_memory_u8(0x4013, length);
+    bus.write_memory_u8(0x4015, 0b0001_0000);
+}
+
+#[test]
+fn dmc_fetch_stalls_the_next_cpu_read() {
+    for parity in 0..2 {
+        let mut nes = nrom_console();
+        let bus = nes.bus_mut();
+        bus.tick(parity);
+
+        start_sample(bus, 0x00, 0);
+        assert_eq!(bus.apu.dmc.dma_address(), Some(0xC040));
+
+        // writes are not halted
+        let cycles = bus.cpu_cycles();
+        bus.write_memory_u8(0x0000, 0x12);
+        assert_eq!(bus.cpu_cycles(), cycles);
+
+        // dummy, fetch and the repeated read, plus one cycle to land the fetch on a get cycle
+        assert_eq!(bus.read_memory_u8(0x0000), 0x12);
+        let stall = bus.cpu_cycles() - cycles;
+        assert!(stall == 3 || stall == 4, "stalled for {} cycles", stall);
+        assert_eq!(bus.cpu_cycles() % 2, 1);
+
+        assert_eq!(bus.apu.dmc.dma_address(), None);
+        assert_eq!(bus.apu.dmc.bytes_remaining, 0);
+    }
+}
+
+#[test]
+fn dmc_interrupt_at_the_end_of_the_sample() {
+    let mut nes = nrom_console();
+    let bus = nes.bus_mut();
+    // only the dmc on /IRQ
+    bus.write_memory_u8(0x4017, 0x40);
+
+    start_sample(bus, 0x80, 1);
+    assert_eq!(bus.apu.dmc.bytes_remaining, 17);
+
+    for _ in 0..16 {
+        bus.read_memory_u8(0x0000);
+        assert!(!bus.poll_irq_interrupt());
+        // one byte per 8 output bits at the slowest rate
+        bus.tick(8 * 428);
+    }
+    bus.read_memory_u8(0x0000);
+    assert_eq!(bus.apu.dmc.bytes_remaining, 0);
+    assert!(bus.poll_irq_interrupt());
+
+    // reading $4015 does not acknowledge it, writing does
+    let status = bus.read_memory_u8(0x4015);
+    assert!(status & ApuStatus::DMC_INTERRUPT.bits() > 0);
+    assert!(bus.poll_irq_interrupt());
+
+    bus.write_memory_u8(0x4015, 0x00);
+    assert!(!bus.poll_irq_interrupt());
+}
+
+#[test]
+fn looping_dmc_sample_restarts_without_interrupt() {
+    let mut nes = nrom_console();
+    let bus = nes.bus_mut();
+
+    start_sample(bus, 0xC0, 0);
+    bus.read_memory_u8(0x0000);
+
+    assert_eq!(bus.apu.dmc.bytes_remaining, 1);
+    assert_eq!(bus.apu.dmc.current_address, 0xC040);
+    assert!(!bus.poll_irq_interrupt());
+}
+
+#[test]
+fn dmc_fetch_during_oam_dma_takes_two_cycles() {
+    let mut nes = nrom_console();
+    let bus = nes.bus_mut();
+
+    let start = bus.cpu_cycles();
+    bus.write_memory_u8(0x4014, 0x02);
+    let plain_dma = bus.cpu_cycles() - start;
+
+    // same parity as the first dma, with a fetch waiting
+    bus.tick(2 - plain_dma % 2);
+    start_sample(bus, 0x00, 0);
+    let start = bus.cpu_cycles();
+    bus.write_memory_u8(0x4014, 0x02);
+    let dma_with_fetch = bus.cpu_cycles() - start;
+
+    assert!(plain_dma == 513 || plain_dma == 514, "oam dma took {} cycles", plain_dma);
+    assert_eq!(dma_with_fetch, plain_dma + 2);
+    assert_eq!(bus.apu.dmc.dma_address(), None);
+}
@@ -0,0 +1,216 @@
+use std::io::Cursor;
+
+use dendynes::{
+    apu::{mixer::mix, APU},
+    clock::MasterClock,
+    frontend::{
+        audio::{rate_adjustment, RingBuffer, MAX_RATE_DEVIATION},
+        wav::{SampleFormat, WavWriter},
+        AudioSink,
+    },
+    region::Region,
+};
+
+// one second of a 50% pulse 1 at `period`, constant volume 15
+fn pulse_second(region: Region, sample_rate: u32, period: u16) -> Vec<f32> {
+    let mut apu = APU::new(region);
+    apu.set_sample_rate(sample_rate);
+    apu.write_register(0x4015, 0b01);
+    apu.write_register(0x4000, 0b1011_1111);
+    apu.write_register(0x4002, period as u8);
+    apu.write_register(0x4003, (period >> 8) as u8);
+
+    let cycles = MasterClock::new(region).cpu_clock_hz().round() as u32;
+    for _ in 0..cycles {
+        apu.tick();
+    }
+
+    return apu.drain_samples().to_vec();
+}
+
+// with some hysteresis, the filtered square wave hovers around zero before each edge
+fn rising_edges(samples: &[f32]) -> usize {
+    let mut edges = 0;
+    let mut high = true;
+
+    for sample in samples.iter() {
+        if high && *sample < -0.05 {
+            high = false;
+        } else if !high && *sample > 0.05 {
+            high = true;
+            edges += 1;
+        }
+    }
+
+    return edges;
+}
+
+#[test]
+fn one_second_of_audio_per_second_of_emulation() {
+    for region in [Region::Ntsc, Region::Pal, Region::Dendy] {
+        for sample_rate in [44_100, 48_000] {
+            let samples = pulse_second(region, sample_rate, 0x100);
+
+            let missing = sample_rate as usize - samples.len();
+            assert!(missing <= 1, "{} at {} Hz: {} samples", region, sample_rate, samples.len());
+        }
+    }
+}
+
+#[test]
+fn pulse_plays_at_the_timer_frequency() {
+    // f = cpu clock / (16 * (period + 1))
+    for (region, period, frequency) in [(Region::Ntsc, 253, 440), (Region::Pal, 235, 440), (Region::Dendy, 251, 440)] {
+        let samples = pulse_second(region, 48_000, period);
+        // the filters need a moment to settle on the new dc level
+        let measured = rising_edges(&samples[4800..]) as f32 / 0.9;
+
+        assert!((measured - frequency as f32).abs() < 3.0, "{}: {} Hz", region, measured);
+    }
+}
+
+#[test]
+fn output_stays_in_range() {
+    let samples = pulse_second(Region::Ntsc, 44_100, 0x20);
+
+    assert!(samples.iter().all(|sample| (-1.0..=1.0).contains(sample)));
+    assert!(samples.iter().any(|sample| sample.abs() > 0.05));
+}
+
+#[test]
+fn mixer_follows_the_nonlinear_tables() {
+    assert_eq!(mix(0, 0, 0, 0, 0), 0.0);
+    assert!((mix(15, 15, 0, 0, 0) - 95.52 / (8128.0 / 30.0 + 100.0)).abs() < 1e-6);
+    // both tables top out at about 1.0 together
+    assert!((mix(15, 15, 15, 15, 127) - 1.0).abs() < 0.01);
+    // two pulses are quieter than twice one
+    assert!(mix(15, 15, 0, 0, 0) < 2.0 * mix(15, 0, 0, 0, 0));
+}
+
+#[test]
+fn wav_header_and_samples() {
+    for (format, bytes_per_sample, wave_format) in [(SampleFormat::I16, 2u16, 1u16), (SampleFormat::F32, 4, 3)] {
+        let mut wav = WavWriter::new(Cursor::new(Vec::new()), 48_000, format).unwrap();
+        wav.queue_samples(&[0.0, 0.5, -1.0]);
+        wav.queue_samples(&[2.0]);
+        assert_eq!(wav.samples_written(), 4);
+
+        let data = wav.finish().unwrap().into_inner();
+        let u16_at = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let u32_at = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+
+        assert_eq!(&data[0..4], b"RIFF");
+        assert_eq!(u32_at(4) as usize, data.len() - 8);
+        assert_eq!(&data[8..16], b"WAVEfmt ");
+        assert_eq!(u16_at(20), wave_format);
+        assert_eq!(u16_at(22), 1);
+        assert_eq!(u32_at(24), 48_000);
+        assert_eq!(u32_at(28), 48_000 * bytes_per_sample as u32);
+        assert_eq!(u16_at(34), bytes_per_sample * 8);
+        assert_eq!(&data[36..40], b"data");
+        assert_eq!(u32_at(40), 4 * bytes_per_sample as u32);
+
+        if format == SampleFormat::I16 {
+            // clipped to full scale
+            assert_eq!(data[44..].chunks(2).map(|pair| i16::from_le_bytes([pair[0], pair[1]])).collect::<Vec<_>>(),
+                vec![0, 16383, -32767, 32767]);
+        }
+    }
+}
+
+#[test]
+fn wav_hash_depends_on_the_samples_only() {
+    let hash = |samples: &[f32], sample_rate: u32| {
+        let mut wav = WavWriter::new(Cursor::new(Vec::new()), sample_rate, SampleFormat::I16).unwrap();
+        wav.queue_samples(samples);
+
+        wav.hash()
+    };
+
+    assert_eq!(hash(&[0.25, -0.25], 44_100), hash(&[0.25, -0.25], 48_000));
+    assert_ne!(hash(&[0.25, -0.25], 44_100), hash(&[-0.25, 0.25], 44_100));
+}
+
+#[test]
+fn ring_buffer_wraps_and_drops_on_overflow() {
+    let mut ring = RingBuffer::new(4);
+    let mut out = [0.0; 4];
+
+    // nothing comes out before the buffer is half full
+    assert_eq!(ring.push(&[1.0]), 1);
+    assert_eq!(ring.pop(&mut out[..1]), 0);
+    assert_eq!(ring.push(&[2.0, 3.0]), 2);
+    assert_eq!(ring.pop(&mut out[..2]), 2);
+    assert_eq!(out[..2], [1.0, 2.0]);
+
+    // wraps around the end, the last sample does not fit
+    assert_eq!(ring.push(&[4.0, 5.0, 6.0, 7.0]), 3);
+    assert_eq!(ring.fill(), 1.0);
+    assert_eq!(ring.pop(&mut out), 4);
+    assert_eq!(out, [3.0, 4.0, 5.0, 6.0]);
+    assert!(ring.is_empty());
+
+    // an underrun waits for half full again
+    ring.push(&[8.0]);
+    assert_eq!(ring.pop(&mut out[..2]), 1);
+    ring.push(&[9.0]);
+    assert_eq!(ring.pop(&mut out[..1]), 0);
+    ring.push(&[10.0]);
+    assert_eq!(ring.pop(&mut out[..2]), 2);
+    assert_eq!(out[..2], [9.0, 10.0]);
+}
+
+#[test]
+fn rate_adjustment_pulls_toward_half_full() {
+    assert_eq!(rate_adjustment(0.5), 1.0);
+    assert_eq!(rate_adjustment(0.0), 1.0 + MAX_RATE_DEVIATION);
+    assert_eq!(rate_adjustment(1.0), 1.0 - MAX_RATE_DEVIATION);
+    assert_eq!(rate_adjustment(7.0), 1.0 - MAX_RATE_DEVIATION);
+}
+
+// frames paced at a rounded update rate into a 48 kHz device with a 100 ms buffer,
+// returns the lowest and highest fill once the control loop settled
+fn paced_fill_range(region: Region, updates_per_second: u32, seconds: u32) -> (f32, f32) {
+    let sample_rate = 48_000;
+    let mut apu = APU::new(region);
+    apu.set_sample_rate(sample_rate);
+    // something to hear
+    apu.write_register(0x4015, 0b01);
+    apu.write_register(0x4000, 0b1011_1111);
+    apu.write_register(0x4002, 0xFD);
+
+    let mut ring = RingBuffer::new(sample_rate as usize / 10);
+    let mut device = vec![0.0; (sample_rate / updates_per_second) as usize];
+    let cycles_per_frame = MasterClock::new(region).cpu_clock_hz() / region.timing().frames_per_second;
+
+    let mut cycles = 0.0;
+    let mut fill_range = (1.0f32, 0.0f32);
+    for frame in 0..seconds * updates_per_second {
+        cycles += cycles_per_frame;
+        while cycles >= 1.0 {
+            apu.tick();
+            cycles -= 1.0;
+        }
+
+        ring.push(apu.drain_samples());
+        apu.set_rate_adjustment(rate_adjustment(ring.fill()));
+        ring.pop(&mut device);
+
+        if frame > 5 * updates_per_second {
+            fill_range = (fill_range.0.min(ring.fill()), fill_range.1.max(ring.fill()));
+        }
+    }
+
+    return fill_range;
+}
+
+#[test]
+fn rate_control_keeps_the_buffer_from_running_dry() {
+    // ntsc is 60.0988 frames a second, a 60 Hz window update falls behind the sound card
+    // without rate control. Dendy and PAL at 50 Hz are close, but not exact either
+    for (region, updates_per_second) in [(Region::Ntsc, 60), (Region::Dendy, 50), (Region::Pal, 50)] {
+        let (low, high) = paced_fill_range(region, updates_per_second, 60);
+
+        assert!(low > 0.1 && high < 0.9, "{} fill between {} and {}", region, low, high);
+    }
+}
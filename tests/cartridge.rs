@@ -0,0 +1,135 @@
+use dendynes::cartridge::{mappers::MapperType, Cartridge, Mirroring, RomError};
+
+const HEADER_SIZE: usize = 16;
+const TRAINER_SIZE: usize = 512;
+const PRG_BANK_SIZE: usize = 0x4000;
+const CHR_BANK_SIZE: usize = 0x2000;
+
+fn header(prg_banks: u8, chr_banks: u8, flags_6: u8, flags_7: u8) -> Vec<u8> {
+    return vec![b'N', b'E', b'S', 0x1A, prg_banks, chr_banks, flags_6, flags_7, 0, 0, 0, 0, 0, 0, 0, 0];
+}
+
+// header followed by `size` bytes of counting data
+fn rom(header: Vec<u8>, size: usize) -> Vec<u8> {
+    let mut rom = header;
+    rom.extend((0..size).map(|offset| offset as u8));
+
+    return rom;
+}
+
+fn load_error(data: &[u8]) -> RomError {
+    return match Cartridge::from_bytes(data) {
+        Ok(_) => panic!("ROM loaded"),
+        Err(error) => error,
+    };
+}
+
+#[test]
+fn nrom_loads() {
+    let cartridge = Cartridge::from_bytes(&rom(header(1, 1, 0b0001, 0), PRG_BANK_SIZE + CHR_BANK_SIZE)).unwrap();
+
+    assert_eq!(cartridge.mapper_type, MapperType::NROM);
+    assert_eq!(cartridge.mirroring, Mirroring::Vertical);
+    assert_eq!(cartridge.prg_memory.len(), PRG_BANK_SIZE);
+    assert_eq!(cartridge.chr_memory.len(), CHR_BANK_SIZE);
+}
+
+#[test]
+fn bad_magic() {
+    let mut data = rom(header(1, 1, 0, 0), PRG_BANK_SIZE + CHR_BANK_SIZE);
+    data[2] = b'Z';
+
+    assert!(matches!(load_error(&data), RomError::BadMagic));
+}
+
+#[test]
+fn truncated_header() {
+    let data = header(1, 1, 0, 0);
+
+    assert!(matches!(load_error(&data[..8]), RomError::Truncated { expected: HEADER_SIZE, got: 8 }));
+}
+
+#[test]
+fn truncated_prg_rom() {
+    let error = load_error(&rom(header(2, 1, 0, 0), PRG_BANK_SIZE));
+
+    match error {
+        RomError::Truncated { expected, got } => {
+            assert_eq!(expected, HEADER_SIZE + 2 * PRG_BANK_SIZE + CHR_BANK_SIZE);
+            assert_eq!(got, HEADER_SIZE + PRG_BANK_SIZE);
+        },
+        other => panic!("expected Truncated, got {:?}", other),
+    }
+}
+
+#[test]
+fn truncated_chr_rom() {
+    let error = load_error(&rom(header(1, 1, 0, 0), PRG_BANK_SIZE + CHR_BANK_SIZE / 2));
+
+    match error {
+        RomError::Truncated { expected, got } => {
+            assert_eq!(expected, HEADER_SIZE + PRG_BANK_SIZE + CHR_BANK_SIZE);
+            assert_eq!(got, HEADER_SIZE + PRG_BANK_SIZE + CHR_BANK_SIZE / 2);
+        },
+        other => panic!("expected Truncated, got {:?}", other),
+    }
+}
+
+#[test]
+fn trainer_counts_towards_the_size() {
+    // the flag says there is a trainer, the file has none
+    let error = load_error(&rom(header(1, 0, 0b0100, 0), PRG_BANK_SIZE));
+
+    match error {
+        RomError::Truncated { expected, got } => {
+            assert_eq!(expected, HEADER_SIZE + TRAINER_SIZE + PRG_BANK_SIZE);
+            assert_eq!(got, HEADER_SIZE + PRG_BANK_SIZE);
+        },
+        other => panic!("expected Truncated, got {:?}", other),
+    }
+
+    // with the trainer PRG starts after it
+    let mut data = header(1, 0, 0b0100, 0);
+    data.extend(vec![0xEE; TRAINER_SIZE]);
+    let cartridge = Cartridge::from_bytes(&rom(data, PRG_BANK_SIZE)).unwrap();
+    assert_eq!(cartridge.prg_memory[..4], [0, 1, 2, 3]);
+    assert_eq!(cartridge.prg_memory.len(), PRG_BANK_SIZE);
+}
+
+#[test]
+fn missing_prg_rom() {
+    assert!(matches!(load_error(&rom(header(0, 1, 0, 0), CHR_BANK_SIZE)), RomError::MissingPrgRom));
+}
+
+#[test]
+fn mapper_32_loads_as_uxrom() {
+    let cartridge = Cartridge::from_bytes(&rom(header(2, 0, 0, 0x20), 2 * PRG_BANK_SIZE)).unwrap();
+
+    assert_eq!(cartridge.mapper_type, MapperType::UxRom);
+}
+
+#[test]
+fn unsupported_mapper() {
+    // MMC3, low nibble in flags 6
+    assert!(matches!(load_error(&rom(header(1, 1, 0x40, 0), PRG_BANK_SIZE + CHR_BANK_SIZE)), RomError::UnsupportedMapper(4)));
+    // high nibble in flags 7
+    assert!(matches!(load_error(&rom(header(1, 1, 0, 0x10), PRG_BANK_SIZE + CHR_BANK_SIZE)), RomError::UnsupportedMapper(16)));
+}
+
+#[test]
+fn nes2_header() {
+    let error = load_error(&rom(header(1, 1, 0, 0b1000), PRG_BANK_SIZE + CHR_BANK_SIZE));
+
+    assert!(matches!(error, RomError::Nes2Unsupported));
+}
+
+#[test]
+fn diskdude_padding_is_ignored() {
+    let mut data = rom(header(1, 1, 0b0001, 0), PRG_BANK_SIZE + CHR_BANK_SIZE);
+    data[7..16].copy_from_slice(b"DiskDude!");
+
+    // flags 7 would read as mapper 64 otherwise
+    let cartridge = Cartridge::from_bytes(&data).unwrap();
+    assert_eq!(cartridge.mapper_type, MapperType::NROM);
+    assert_eq!(cartridge.mirroring, Mirroring::Vertical);
+}
@@ -0,0 +1,219 @@
+use std::{env, fs, path::PathBuf};
+
+use dendynes::{
+    apu::DEFAULT_SAMPLE_RATE,
+    bus::joypad::{Joypad, JoypadButtons},
+    cartridge::Cartridge,
+    cpu::fault::CpuFaultKind,
+    frontend::{
+        config::ConfigError,
+        headless::{frame_hash, run_frames, save_screenshot, HeadlessOptions, InputEvent, InputScript, DEFAULT_FRAMES},
+        wav::SampleFormat,
+        NullSink, VideoSink,
+    },
+    nes::{FrameBuffer, Nes},
+    power_on::PowerOnRam,
+    ppu::{PALETTE, SCREEN_HEIGHT, SCREEN_WIDTH},
+    region::Region,
+};
+
+// waits for three vblanks, then jams
+const JAM_PROGRAM: [u8; 11] = [
+    0xA2, 0x03,       // LDX #$03
+    0x2C, 0x02, 0x20, // BIT $2002
+    0x10, 0xFB,       // BPL -5
+    0xCA,             // DEX
+    0xD0, 0xF8,       // BNE -8
+    0x02,             // JAM
+];
+
+fn jamming_console() -> Nes {
+    let mut prg = vec![0xEA; 0x4000];
+    prg[..JAM_PROGRAM.len()].copy_from_slice(&JAM_PROGRAM);
+    // reset vector at $8000, mirrored at $FFFC
+    prg[0x3FFC] = 0x00;
+    prg[0x3FFD] = 0x80;
+
+    let mut rom = vec![b'N', b'E', b'S', 0x1A, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    rom.extend(prg);
+    rom.extend(vec![0; 0x2000]);
+
+    return Nes::new(Cartridge::from_bytes(&rom).unwrap());
+}
+
+struct FrameCounter(u32);
+
+impl VideoSink for FrameCounter {
+    fn present(&mut self, _frame: &FrameBuffer) {
+        self.0 += 1;
+    }
+}
+
+#[test]
+fn run_frames_counts_completed_frames() {
+    let mut nes = jamming_console();
+    let mut video = FrameCounter(0);
+
+    let (frames, result) = run_frames(&mut nes, 60, &mut InputScript::default(), &mut video, &mut NullSink);
+
+    assert_eq!(result.unwrap_err().kind, CpuFaultKind::Jammed);
+    assert_eq!(frames, video.0);
+    assert!(frames > 0 && frames < 5, "{} frames", frames);
+}
+
+#[test]
+fn input_script_parses_events_in_frame_order() {
+    let script = InputScript::parse("\
+# title screen
+120 2 a+right   # both at once
+
+60  1 START
+62  1 -
+").unwrap();
+
+    assert_eq!(script.events, vec![
+        InputEvent { frame: 60, player: 0, buttons: JoypadButtons::START },
+        InputEvent { frame: 62, player: 0, buttons: JoypadButtons::empty() },
+        InputEvent { frame: 120, player: 1, buttons: JoypadButtons::A | JoypadButtons::RIGHT },
+    ]);
+}
+
+#[test]
+fn input_script_holds_buttons_until_released() {
+    let script = InputScript::parse("1 1 A+B\n3 1 -\n4 2 SELECT\n5 2\n").unwrap();
+    let mut joypads = [Joypad::new(), Joypad::new()];
+    let mut held = Vec::new();
+
+    for frame in 0..6 {
+        script.apply(frame, &mut joypads);
+        held.push((joypads[0].buttons_pressed, joypads[1].buttons_pressed));
+    }
+
+    let none = JoypadButtons::empty();
+    let a_b = JoypadButtons::A | JoypadButtons::B;
+    assert_eq!(held, vec![
+        (none, none),
+        (a_b, none),
+        (a_b, none),
+        (none, none),
+        (none, JoypadButtons::SELECT),
+        // a missing button column releases too
+        (none, none),
+    ]);
+}
+
+#[test]
+fn input_script_errors_carry_the_line_number() {
+    let error = |source: &str| InputScript::parse(source).unwrap_err();
+
+    for player in ["0", "3", "x", ""] {
+        let script_error = error(&format!("# comment\n\n10 1 A\n20 {} A\n", player));
+        assert_eq!(script_error.line, 4, "player {:?}", player);
+        assert_eq!(script_error.message, "player must be 1 or 2");
+    }
+
+    let script_error = error("10 1 A\n20 1 A+TURBO\n");
+    assert_eq!(script_error.line, 2);
+    assert_eq!(script_error.message, "unknown button \"TURBO\"");
+    assert_eq!(script_error.to_string(), "input script line 2: unknown button \"TURBO\"");
+
+    assert_eq!(error("soon 1 A").line, 1);
+    assert_eq!(error("\n10 1 A B").line, 2);
+}
+
+fn parse_options(arguments: &[&str]) -> Result<HeadlessOptions, ConfigError> {
+    return HeadlessOptions::parse(arguments.iter().map(|argument| argument.to_string()));
+}
+
+#[test]
+fn headless_options_defaults() {
+    let options = parse_options(&["game.nes"]).unwrap();
+
+    assert_eq!(options, HeadlessOptions {
+        rom_path: PathBuf::from("game.nes"),
+        frames: DEFAULT_FRAMES,
+        region: Region::Ntsc,
+        power_on_ram: PowerOnRam::Zeros,
+        input_script_path: None,
+        screenshot_path: None,
+        wav_path: None,
+        sample_rate: DEFAULT_SAMPLE_RATE,
+        sample_format: SampleFormat::I16,
+    });
+}
+
+#[test]
+fn headless_options_parse_every_option() {
+    let options = parse_options(&[
+        "--frames", "900", "--region", "dendy", "--power-on-ram", "random:5", "game.nes",
+        "--input", "script.txt", "--screenshot", "last.png", "--wav", "run.wav",
+        "--sample-rate", "48000", "--sample-format", "f32",
+    ]).unwrap();
+
+    assert_eq!(options, HeadlessOptions {
+        rom_path: PathBuf::from("game.nes"),
+        frames: 900,
+        region: Region::Dendy,
+        power_on_ram: PowerOnRam::Random(Some(5)),
+        input_script_path: Some(PathBuf::from("script.txt")),
+        screenshot_path: Some(PathBuf::from("last.png")),
+        wav_path: Some(PathBuf::from("run.wav")),
+        sample_rate: 48_000,
+        sample_format: SampleFormat::F32,
+    });
+}
+
+#[test]
+fn bad_headless_options_are_usage_errors() {
+    let usage = |arguments: &[&str]| match parse_options(arguments) {
+        Err(ConfigError::Usage(message)) => message,
+        other => panic!("{:?}: expected a usage error, got {:?}", arguments, other),
+    };
+
+    assert_eq!(usage(&[]), "missing ROM path");
+    assert_eq!(usage(&["a.nes", "b.nes"]), "only one ROM can be given");
+    assert_eq!(usage(&["a.nes", "--frames"]), "--frames expects a value");
+    assert_eq!(usage(&["a.nes", "--frames", "-1"]), "bad frame count \"-1\"");
+    assert_eq!(usage(&["a.nes", "--sample-rate", "0"]), "bad sample rate \"0\"");
+    assert_eq!(usage(&["a.nes", "--turbo"]), "unknown option --turbo");
+    usage(&["a.nes", "--region", "secam"]);
+    usage(&["a.nes", "--power-on-ram", "random:x"]);
+    usage(&["a.nes", "--sample-format", "u8"]);
+}
+
+// palette index (x + y) & 0x3F, every color shows up
+fn gradient_frame() -> FrameBuffer {
+    let mut frame = [[0; SCREEN_WIDTH]; SCREEN_HEIGHT];
+
+    for (y, row) in frame.iter_mut().enumerate() {
+        for (x, pixel) in row.iter_mut().enumerate() {
+            *pixel = ((x + y) & 0x3F) as u8;
+        }
+    }
+
+    return frame;
+}
+
+#[test]
+fn frame_hash_is_fnv_1a_over_palette_indices() {
+    // CI compares these, they must not change between versions or platforms
+    assert_eq!(frame_hash(&[[0; SCREEN_WIDTH]; SCREEN_HEIGHT]), 0x3fd4_ebc4_ab9c_e325);
+    assert_eq!(frame_hash(&gradient_frame()), 0x1838_5e2c_72e4_8125);
+}
+
+#[test]
+fn screenshot_round_trips_through_png() {
+    let frame = gradient_frame();
+    let path = env::temp_dir().join(format!("dendynes-{}-screenshot.png", std::process::id()));
+
+    save_screenshot(&frame, &path).unwrap();
+    let image = image::open(&path).unwrap().to_rgba8();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(image.dimensions(), (SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32));
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let color = PALETTE[frame[y as usize][x as usize] as usize];
+
+        assert_eq!(pixel.0, [color[0], color[1], color[2], 255], "pixel {}, {}", x, y);
+    }
+}
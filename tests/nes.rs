@@ -0,0 +1,166 @@
+use dendynes::{
+    apu::ApuStatus,
+    cartridge::Cartridge,
+    cpu::processor::StatusFlags,
+    nes::Nes,
+    power_on::PowerOnRam,
+    region::Region,
+};
+
+const PROGRAM_START: usize = 0x8000;
+
+// touches ram, the ppu registers and pulse 1, then spins with interrupts enabled
+const PROGRAM: [u8; 42] = [
+    0xA9, 0x40,       // LDA #$40
+    0x8D, 0x17, 0x40, // STA $4017, no frame interrupt
+    0x58,             // CLI
+    0xA9, 0xAB,       // LDA #$AB
+    0x8D, 0x00, 0x03, // STA $0300
+    0xA9, 0x04,       // LDA #$04
+    0x8D, 0x00, 0x20, // STA $2000
+    0xA9, 0x1E,       // LDA #$1E
+    0x8D, 0x01, 0x20, // STA $2001
+    0x8D, 0x05, 0x20, // STA $2005, first write sets the latch
+    0xA9, 0x01,       // LDA #$01
+    0x8D, 0x15, 0x40, // STA $4015
+    0xA9, 0xBF,       // LDA #$BF
+    0x8D, 0x00, 0x40, // STA $4000
+    0xA9, 0x08,       // LDA #$08
+    0x8D, 0x03, 0x40, // STA $4003, loads the length counter
+    0x4C, 0x27, 0x80, // JMP $8027
+];
+const SPIN_ADDRESS: usize = 0x8027;
+
+fn console(power_on_ram: PowerOnRam) -> Nes {
+    let mut prg = vec![0xEA; 0x4000];
+    prg[..PROGRAM.len()].copy_from_slice(&PROGRAM);
+    // reset vector, mirrored at $FFFC
+    prg[0x3FFC] = 0x00;
+    prg[0x3FFD] = 0x80;
+
+    let mut rom = vec![b'N', b'E', b'S', 0x1A, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    rom.extend(prg);
+    rom.extend(vec![0; 0x2000]);
+
+    return Nes::with_power_on_ram(Cartridge::from_bytes(&rom).unwrap(), Region::Ntsc, power_on_ram);
+}
+
+fn run_program(nes: &mut Nes) {
+    assert_eq!(nes.cpu.program_pointer, PROGRAM_START);
+
+    for _ in 0..100 {
+        if nes.cpu.program_pointer == SPIN_ADDRESS {
+            return;
+        }
+        nes.step_instruction().unwrap();
+    }
+
+    panic!("program did not reach ${:04X}", SPIN_ADDRESS);
+}
+
+#[test]
+fn soft_reset_keeps_ram_and_clears_registers() {
+    let mut nes = console(PowerOnRam::Zeros);
+    run_program(&mut nes);
+
+    assert_eq!(nes.bus().cpu_memory[0x0300], 0xAB);
+    assert!(!nes.cpu.status.contains(StatusFlags::INTERRUPT_DISABLE));
+    assert!(!nes.ppu().control_register.is_empty());
+    assert!(!nes.ppu().mask_register.is_empty());
+    assert!(nes.ppu().latch);
+    assert_eq!(nes.bus().apu.peek_status() & ApuStatus::PULSE_1.bits(), ApuStatus::PULSE_1.bits());
+
+    let stack_pointer = nes.cpu.stack_pointer;
+    let register_a = nes.cpu.register_a;
+    nes.soft_reset();
+
+    assert_eq!(nes.bus().cpu_memory[0x0300], 0xAB);
+    assert_eq!(nes.cpu.stack_pointer, stack_pointer.wrapping_sub(3));
+    assert_eq!(nes.cpu.register_a, register_a);
+    assert!(nes.cpu.status.contains(StatusFlags::INTERRUPT_DISABLE));
+    assert_eq!(nes.cpu.program_pointer, PROGRAM_START);
+
+    assert!(nes.ppu().control_register.is_empty());
+    assert!(nes.ppu().mask_register.is_empty());
+    assert!(!nes.ppu().latch);
+    assert_eq!(nes.bus().apu.peek_status(), 0);
+}
+
+#[test]
+fn power_cycle_refills_every_memory() {
+    let mut nes = console(PowerOnRam::Ones);
+    run_program(&mut nes);
+    nes.bus_mut().cpu_memory[0x0010] = 0x12;
+    nes.ppu_mut().memory[0x0100] = 0x34;
+    nes.ppu_mut().oam_data[0x20] = 0x56;
+    nes.ppu_mut().palette[0x05] = 0x07;
+
+    nes.power_cycle();
+
+    assert!(nes.bus().cpu_memory.iter().all(|byte| *byte == 0xFF));
+    assert!(nes.ppu().memory.iter().all(|byte| *byte == 0xFF));
+    assert!(nes.ppu().oam_data.iter().all(|byte| *byte == 0xFF));
+    // palette entries are 6 bits wide
+    assert!(nes.ppu().palette.iter().all(|byte| *byte == 0x3F));
+    assert_eq!(nes.cpu.stack_pointer, 0xFD);
+    assert_eq!(nes.bus().apu.peek_status(), 0);
+
+    // the program runs the same way again
+    run_program(&mut nes);
+    assert_eq!(nes.bus().cpu_memory[0x0300], 0xAB);
+}
+
+#[test]
+fn seeded_power_on_ram_repeats() {
+    let mut nes = console(PowerOnRam::Random(Some(42)));
+    let fresh = console(PowerOnRam::Random(Some(42)));
+    let other_seed = console(PowerOnRam::Random(Some(43)));
+
+    assert_eq!(nes.bus().cpu_memory, fresh.bus().cpu_memory);
+    assert_ne!(nes.bus().cpu_memory, other_seed.bus().cpu_memory);
+
+    run_program(&mut nes);
+    nes.power_cycle();
+    assert_eq!(nes.bus().cpu_memory, fresh.bus().cpu_memory);
+    assert_eq!(nes.ppu().memory, fresh.ppu().memory);
+    assert_eq!(nes.ppu().oam_data, fresh.ppu().oam_data);
+    assert_eq!(nes.ppu().palette, fresh.ppu().palette);
+
+    assert!(fresh.ppu().palette.iter().all(|byte| *byte <= 0x3F));
+    assert!(fresh.ppu().palette.iter().any(|byte| *byte != 0));
+}
+
+#[test]
+fn fill_patterns() {
+    let mut memory = [0x55u8; 13];
+
+    PowerOnRam::Zeros.fill(&mut memory, 0);
+    assert_eq!(memory, [0x00; 13]);
+    PowerOnRam::Ones.fill(&mut memory, 0);
+    assert_eq!(memory, [0xFF; 13]);
+
+    let random = |seed: u64, stream: u64| {
+        let mut memory = [0u8; 13];
+        PowerOnRam::Random(Some(seed)).fill(&mut memory, stream);
+
+        memory
+    };
+    assert_eq!(random(7, 0), random(7, 0));
+    assert_ne!(random(7, 0), random(7, 1));
+    assert_ne!(random(7, 0), random(8, 0));
+    assert!(random(7, 0).iter().any(|byte| *byte != 0));
+}
+
+#[test]
+fn power_on_ram_names_round_trip() {
+    for ram in [PowerOnRam::Zeros, PowerOnRam::Ones, PowerOnRam::Random(None), PowerOnRam::Random(Some(12345))] {
+        assert_eq!(ram.to_string().parse::<PowerOnRam>(), Ok(ram));
+    }
+
+    assert_eq!(PowerOnRam::Random(Some(42)).to_string(), "random:42");
+    assert_eq!("RANDOM:42".parse::<PowerOnRam>(), Ok(PowerOnRam::Random(Some(42))));
+    assert_eq!("00".parse::<PowerOnRam>(), Ok(PowerOnRam::Zeros));
+    assert_eq!("ff".parse::<PowerOnRam>(), Ok(PowerOnRam::Ones));
+    assert!("random:soon".parse::<PowerOnRam>().is_err());
+    assert!("noise".parse::<PowerOnRam>().is_err());
+}
@@ -0,0 +1,122 @@
+//! nestest in automation mode: start at $C000 with the registers of the published log,
+//! trace every instruction and compare against `tests/roms/nestest.log`.
+//!
+//! The log uses the columns of the well known nestest.log without the disassembly, lines with it
+//! are read the same way, so the original log can be dropped in place of ours.
+
+use std::{fs, path::PathBuf};
+
+use dendynes::{
+    cartridge::Cartridge,
+    cpu::{bus::CpuBus, processor::StatusFlags},
+    nes::Nes,
+};
+
+const AUTOMATION_START: usize = 0xC000;
+const POWER_UP_STATUS: u8 = 0x24;
+// $02 holds the code of the first failed official opcode test, $03 the unofficial one
+const OFFICIAL_RESULT_ADDRESS: usize = 0x02;
+const UNOFFICIAL_RESULT_ADDRESS: usize = 0x03;
+
+/// Columns of one trace line
+#[derive(Debug, PartialEq, Eq)]
+struct TraceLine {
+    program_pointer: String,
+    bytes: String,
+    a: String,
+    x: String,
+    y: String,
+    p: String,
+    sp: String,
+    ppu: String,
+    cycles: String,
+}
+
+impl TraceLine {
+    fn parse(line: &str) -> Option<Self> {
+        let registers = &line[line.find(" A:")?..];
+        let keys = [" A:", " X:", " Y:", " P:", " SP:", " PPU:", " CYC:"];
+        let starts: Vec<usize> = keys.iter().filter_map(|key| registers.find(key)).collect();
+        // a column runs up to the next key, the PPU one is optional
+        let field = |key: &str| -> Option<String> {
+            let key_start = registers.find(key)?;
+            let start = key_start + key.len();
+            let end = starts.iter().copied().filter(|next| *next > key_start).min().unwrap_or(registers.len());
+
+            return Some(registers[start..end].trim().to_string());
+        };
+
+        return Some(TraceLine {
+            program_pointer: line.get(0..4)?.to_string(),
+            bytes: line.get(6..14)?.trim().to_string(),
+            a: field(" A:")?,
+            x: field(" X:")?,
+            y: field(" Y:")?,
+            p: field(" P:")?,
+            sp: field(" SP:")?,
+            ppu: field(" PPU:").unwrap_or_default(),
+            cycles: field(" CYC:")?,
+        });
+    }
+
+    fn differences(&self, other: &TraceLine) -> Vec<String> {
+        let columns = [
+            ("PC", &self.program_pointer, &other.program_pointer),
+            ("bytes", &self.bytes, &other.bytes),
+            ("A", &self.a, &other.a),
+            ("X", &self.x, &other.x),
+            ("Y", &self.y, &other.y),
+            ("P", &self.p, &other.p),
+            ("SP", &self.sp, &other.sp),
+            ("PPU scanline,dot", &self.ppu, &other.ppu),
+            ("CYC", &self.cycles, &other.cycles),
+        ];
+
+        return columns.iter()
+            .filter(|(_, expected, actual)| expected != actual)
+            .map(|(column, expected, actual)| format!("{} expected {}, got {}", column, expected, actual))
+            .collect();
+    }
+}
+
+fn roms_directory() -> PathBuf {
+    return PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/roms");
+}
+
+#[test]
+fn nestest_matches_reference_log() {
+    let reference = fs::read_to_string(roms_directory().join("nestest.log")).expect("nestest.log is in the repo");
+    let reference: Vec<&str> = reference.lines().filter(|line| !line.trim().is_empty()).collect();
+
+    let cartridge = Cartridge::from_path(roms_directory().join("nestest.nes")).expect("nestest.nes is in the repo");
+    let mut nes = Nes::new(cartridge);
+    nes.cpu.program_pointer = AUTOMATION_START;
+    nes.cpu.status = StatusFlags::from_bits_truncate(POWER_UP_STATUS);
+
+    let mut trace = Vec::with_capacity(reference.len());
+    for _ in 0..reference.len() {
+        trace.push(nes.cpu.trace_line());
+
+        if let Err(fault) = nes.step_instruction() {
+            panic!("nestest faulted after {} instructions: {}", trace.len(), fault);
+        }
+    }
+
+    for (line_number, (expected, actual)) in reference.iter().zip(trace.iter()).enumerate() {
+        let expected_line = TraceLine::parse(expected)
+            .unwrap_or_else(|| panic!("nestest.log:{} is not a trace line: {}", line_number + 1, expected));
+        let actual_line = TraceLine::parse(actual).expect("trace_line writes the log layout");
+
+        let differences = expected_line.differences(&actual_line);
+        assert!(
+            differences.is_empty(),
+            "trace diverges at nestest.log:{}\nexpected: {}\n     got: {}\n{}",
+            line_number + 1, expected, actual, differences.join("\n"),
+        );
+    }
+
+    let official = nes.bus().peek_u8(OFFICIAL_RESULT_ADDRESS);
+    let unofficial = nes.bus().peek_u8(UNOFFICIAL_RESULT_ADDRESS);
+    assert_eq!(official, 0x00, "official opcode test failed with code ${:02X}", official);
+    assert_eq!(unofficial, 0x00, "unofficial opcode test failed with code ${:02X}", unofficial);
+}
@@ -0,0 +1,287 @@
+//! Per-instruction conformance against the JSON single step vectors of
+//! https://github.com/SingleStepTests/65x02 (the `nes6502` set: 2A03, no decimal mode).
+//!
+//! A few hand checked vectors in `tests/single_step_fixture/` run with every `cargo test`.
+//! The full set is not in the repo, put `00.json`..`ff.json` into `tests/single_step/`
+//! or point `DENDYNES_SINGLE_STEP_DIR` at them and run `cargo test -- --ignored`.
+//! `DENDYNES_SINGLE_STEP_OPCODES=a9,6d` limits the run to a few opcodes.
+//! The `6502` set with decimal mode runs with `DENDYNES_SINGLE_STEP_VARIANT=6502`.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use dendynes::cpu::{
+    bus::{CpuBus, FlatRam},
+    fault::CpuFaultKind,
+    processor::{StatusFlags, CPU},
+    variant::CpuVariant,
+};
+
+const DEFAULT_VECTORS_DIRECTORY: &str = "tests/single_step";
+const FIXTURE_DIRECTORY: &str = "tests/single_step_fixture";
+// failures printed per opcode, the rest is only counted
+const REPORTED_FAILURES: usize = 3;
+
+#[derive(Deserialize)]
+struct CpuState {
+    pc: u16,
+    s: u8,
+    a: u8,
+    x: u8,
+    y: u8,
+    p: u8,
+    ram: Vec<(u16, u8)>,
+}
+
+#[derive(Deserialize)]
+struct TestVector {
+    name: String,
+    initial: CpuState,
+    #[serde(rename = "final")]
+    expected: CpuState,
+    cycles: Vec<(u16, u8, String)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BusAccess {
+    address: u16,
+    value: u8,
+    write: bool,
+}
+
+impl BusAccess {
+    fn describe(&self) -> String {
+        let kind = if self.write { "write" } else { "read" };
+
+        return format!("{} ${:04X}={:02X}", kind, self.address, self.value);
+    }
+}
+
+/// Flat RAM that remembers every access, the vectors list one per cycle
+struct RecordingBus {
+    ram: FlatRam,
+    accesses: Vec<BusAccess>,
+}
+
+impl CpuBus for RecordingBus {
+    fn read_memory_u8(&mut self, index: usize) -> u8 {
+        let value = self.ram.read_memory_u8(index);
+        self.accesses.push(BusAccess { address: index as u16, value: value, write: false });
+
+        return value;
+    }
+
+    fn write_memory_u8(&mut self, index: usize, value: u8) {
+        self.ram.write_memory_u8(index, value);
+        self.accesses.push(BusAccess { address: index as u16, value: value, write: true });
+    }
+
+    fn peek_u8(&self, index: usize) -> u8 {
+        return self.ram.peek_u8(index);
+    }
+
+    fn tick(&mut self, cycles: u64) {
+        self.ram.tick(cycles);
+    }
+
+    fn cpu_cycles(&self) -> u64 {
+        return self.ram.cpu_cycles();
+    }
+
+    fn poll_nmi_interrupt(&self) -> bool {
+        return false;
+    }
+
+    fn poll_irq_interrupt(&self) -> bool {
+        return false;
+    }
+}
+
+fn vectors_directory() -> PathBuf {
+    return match env::var("DENDYNES_SINGLE_STEP_DIR") {
+        Ok(directory) => PathBuf::from(directory),
+        Err(_) => PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(DEFAULT_VECTORS_DIRECTORY),
+    };
+}
+
+fn selected_opcodes() -> Vec<u8> {
+    return match env::var("DENDYNES_SINGLE_STEP_OPCODES") {
+        Ok(list) => list
+            .split(',')
+            .map(|opcode| u8::from_str_radix(opcode.trim(), 16).expect("opcodes are hex bytes"))
+            .collect(),
+        Err(_) => (0..=0xFF).collect(),
+    };
+}
+
+fn selected_variant() -> CpuVariant {
+    return match env::var("DENDYNES_SINGLE_STEP_VARIANT") {
+        Ok(variant) => variant.parse().unwrap(),
+        Err(_) => CpuVariant::Ricoh2A03,
+    };
+}
+
+/// Runs one vector, returns every difference found or None when the cpu jammed on purpose
+fn run_vector(vector: &TestVector, variant: CpuVariant) -> Option<Vec<String>> {
+    let mut bus = RecordingBus { ram: FlatRam::new(), accesses: Vec::new() };
+    for (address, value) in &vector.initial.ram {
+        bus.ram.memory[*address as usize] = *value;
+    }
+
+    let mut cpu = CPU::with_variant(bus, variant);
+    cpu.program_pointer = vector.initial.pc as usize;
+    cpu.stack_pointer = vector.initial.s;
+    cpu.register_a = vector.initial.a;
+    cpu.register_x = vector.initial.x;
+    cpu.register_y = vector.initial.y;
+    cpu.status = StatusFlags::from_bits_truncate(vector.initial.p);
+    cpu.bus.accesses.clear();
+
+    let mut mismatches = Vec::new();
+
+    match cpu.execute_opcode() {
+        Ok(_) => {},
+        Err(fault) if fault.kind == CpuFaultKind::Jammed => {
+            return None;
+        },
+        Err(fault) => {
+            mismatches.push(format!("faulted: {}", fault));
+        },
+    }
+
+    let expected = &vector.expected;
+    let registers = [
+        ("PC", expected.pc, cpu.program_pointer as u16),
+        ("SP", expected.s as u16, cpu.stack_pointer as u16),
+        ("A", expected.a as u16, cpu.register_a as u16),
+        ("X", expected.x as u16, cpu.register_x as u16),
+        ("Y", expected.y as u16, cpu.register_y as u16),
+    ];
+    for (register, expected, actual) in registers {
+        if expected != actual {
+            mismatches.push(format!("{} expected {:02X}, got {:02X}", register, expected, actual));
+        }
+    }
+
+    // B and bit 5 only exist in the copies pushed to the stack, which the ram check covers
+    let flags_mask = !(StatusFlags::BREAK | StatusFlags::UNUSED).bits();
+    if expected.p & flags_mask != cpu.status.bits() & flags_mask {
+        mismatches.push(format!(
+            "P expected {:08b}, got {:08b} (NV-BDIZC)", expected.p & flags_mask, cpu.status.bits() & flags_mask
+        ));
+    }
+
+    for (address, value) in &expected.ram {
+        let actual = cpu.bus.ram.memory[*address as usize];
+
+        if actual != *value {
+            mismatches.push(format!("ram ${:04X} expected {:02X}, got {:02X}", address, value, actual));
+        }
+    }
+
+    let expected_accesses: Vec<BusAccess> = vector.cycles.iter()
+        .map(|(address, value, kind)| BusAccess { address: *address, value: *value, write: kind == "write" })
+        .collect();
+    let accesses = &cpu.bus.accesses;
+
+    for cycle in 0..expected_accesses.len().max(accesses.len()) {
+        let expected = expected_accesses.get(cycle).map(BusAccess::describe);
+        let actual = accesses.get(cycle).map(BusAccess::describe);
+
+        if expected != actual {
+            mismatches.push(format!(
+                "cycle {} expected {}, got {}",
+                cycle + 1,
+                expected.unwrap_or("nothing".to_string()),
+                actual.unwrap_or("nothing".to_string()),
+            ));
+        }
+    }
+    if expected_accesses.len() != accesses.len() {
+        mismatches.push(format!("took {} cycles instead of {}", accesses.len(), expected_accesses.len()));
+    }
+
+    return Some(mismatches);
+}
+
+/// Runs the `<opcode>.json` files of `directory` and fails on any mismatch
+fn run_directory(directory: &Path, opcodes: &[u8], variant: CpuVariant) {
+    let mut total = 0;
+    let mut failed = 0;
+    let mut skipped = 0;
+    let mut failed_opcodes = Vec::new();
+
+    for &opcode in opcodes {
+        let path = directory.join(format!("{:02x}.json", opcode));
+        let data = match fs::read_to_string(&path) {
+            Ok(data) => data,
+            Err(_) => {
+                eprintln!("{:02X}: no {}", opcode, path.display());
+                continue;
+            },
+        };
+        let vectors: Vec<TestVector> = serde_json::from_str(&data)
+            .unwrap_or_else(|error| panic!("bad vectors in {}: {}", path.display(), error));
+
+        let mut opcode_failed = 0;
+
+        for vector in &vectors {
+            total += 1;
+
+            let mismatches = match run_vector(vector, variant) {
+                Some(mismatches) => mismatches,
+                None => {
+                    skipped += 1;
+                    continue;
+                },
+            };
+            if mismatches.is_empty() {
+                continue;
+            }
+
+            if opcode_failed < REPORTED_FAILURES {
+                eprintln!("{:02X} [{}]:", opcode, vector.name);
+                for mismatch in &mismatches {
+                    eprintln!("    {}", mismatch);
+                }
+            }
+            opcode_failed += 1;
+        }
+
+        if opcode_failed > 0 {
+            eprintln!("{:02X}: {} of {} vectors failed", opcode, opcode_failed, vectors.len());
+            failed_opcodes.push(format!("{:02X}", opcode));
+        }
+        failed += opcode_failed;
+    }
+
+    eprintln!("single step ({}): {} vectors, {} failed, {} skipped (jam)", variant, total, failed, skipped);
+    assert!(failed == 0, "{} vectors failed in opcodes {}", failed, failed_opcodes.join(" "));
+}
+
+#[test]
+fn single_step_fixture() {
+    let directory = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(FIXTURE_DIRECTORY);
+    let mut opcodes: Vec<u8> = fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter_map(|path| u8::from_str_radix(path.file_stem()?.to_str()?, 16).ok())
+        .collect();
+    opcodes.sort();
+
+    assert!(!opcodes.is_empty(), "no vectors in {}", directory.display());
+    run_directory(&directory, &opcodes, CpuVariant::Ricoh2A03);
+}
+
+#[test]
+#[ignore = "needs the full single step vectors, see the top of this file"]
+fn single_step_vectors() {
+    let directory = vectors_directory();
+
+    assert!(directory.is_dir(), "single step vectors not found in {}", directory.display());
+    run_directory(&directory, &selected_opcodes(), selected_variant());
+}